@@ -11,7 +11,7 @@ use solana_sdk::{
     account_info::AccountInfo,
     pubkey::{Pubkey},
     pubkey,
-    sysvar::{recent_blockhashes, Sysvar}, rent::Rent,
+    sysvar::{instructions, slot_hashes, Sysvar}, rent::Rent,
 };
 use solana_sdk::entrypoint::MAX_PERMITTED_DATA_INCREASE;
 use evm_loader::{
@@ -100,10 +100,14 @@ pub struct EmulatorAccountStorage<'a> {
     block_timestamp: i64,
     neon_token_mint: Pubkey,
     chain_id: u64,
+    /// Stand-in for the Neon address of the operator, which executes the transaction
+    coinbase: H160,
+    /// Ed25519 program instructions which the operator must add to the transaction
+    pub ed25519_instructions: RefCell<Vec<Vec<u8>>>,
 }
 
 impl<'a> EmulatorAccountStorage<'a> {
-    pub fn new(config: &'a Config, token_mint: Pubkey, chain_id: u64) -> EmulatorAccountStorage {
+    pub fn new(config: &'a Config, token_mint: Pubkey, chain_id: u64, coinbase: H160) -> EmulatorAccountStorage {
         trace!("backend::new");
 
         let slot = config.rpc_client.get_slot().unwrap_or_default();
@@ -117,6 +121,8 @@ impl<'a> EmulatorAccountStorage<'a> {
             block_timestamp: timestamp,
             neon_token_mint: token_mint,
             chain_id,
+            coinbase,
+            ed25519_instructions: RefCell::new(Vec::new()),
         }
     }
 
@@ -175,6 +181,21 @@ impl<'a> EmulatorAccountStorage<'a> {
         }
     }
 
+    /// Adds accounts required to burn the EIP-1559 base fee from the deposit pool
+    pub fn add_base_fee_accounts(&self) {
+        let token_program = self.config.rpc_client.get_account(&self.neon_token_mint)
+            .map_or(spl_token::id(), |mint| mint.owner);
+        let (authority, _) = Pubkey::find_program_address(&[b"Deposit"], &self.config.evm_loader);
+        let pool = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &authority, &self.neon_token_mint, &token_program
+        );
+
+        self.add_solana_account(pool, true);
+        self.add_solana_account(self.neon_token_mint, true);
+        self.add_solana_account(authority, false);
+        self.add_solana_account(token_program, false);
+    }
+
     #[must_use]
     pub fn apply_actions(&self, actions: Vec<Action>) -> u64 {
         let mut gas = 0_u64;
@@ -331,18 +352,32 @@ impl<'a> AccountStorage for EmulatorAccountStorage<'a> {
     fn block_hash(&self, number: U256) -> H256 { 
         info!("block_hash {}", number);
 
-        self.add_solana_account(recent_blockhashes::ID, false);
-
         if self.block_number <= number.as_u64() {
             return H256::default();
         }
 
-        if let Ok(timestamp) = self.config.rpc_client.get_block(number.as_u64()) {
-            H256::from_slice(&bs58::decode(timestamp.blockhash).into_vec().unwrap())
-        } else {
-            warn!("Got error trying to get block hash");
-            H256::default()
-        }
+        let slot_hashes = self.clone_solana_account(&slot_hashes::ID);
+        evm_loader::account::sysvar::find_slot_hash(&slot_hashes.data, number.as_u64())
+            .unwrap_or_else(|| {
+                warn!("Slot {} is not found in SlotHashes", number);
+                H256::default()
+            })
+    }
+
+    fn block_coinbase(&self) -> H160 {
+        info!("block_coinbase");
+        self.coinbase
+    }
+
+    fn block_prevrandao(&self) -> H256 {
+        info!("block_prevrandao");
+
+        let slot_hashes = self.clone_solana_account(&slot_hashes::ID);
+        evm_loader::account::sysvar::latest_slot_hash(&slot_hashes.data)
+            .unwrap_or_else(|| {
+                warn!("SlotHashes is empty");
+                H256::default()
+            })
     }
 
     fn exists(&self, address: &H160) -> bool {
@@ -472,6 +507,40 @@ impl<'a> AccountStorage for EmulatorAccountStorage<'a> {
         self.chain_id
     }
 
+    fn ed25519_verify(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        info!("ed25519_verify {}", bs58::encode(pubkey).into_string());
+
+        // On-chain the signature is looked up in the Ed25519 program instructions
+        self.add_solana_account(instructions::ID, false);
+
+        let public_key = match ed25519_dalek::PublicKey::from_bytes(pubkey) {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        };
+        let signature = match ed25519_dalek::Signature::from_bytes(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+        if public_key.verify_strict(message, &signature).is_err() {
+            return false;
+        }
+
+        // On-chain the signature is accepted only with the Ed25519 instruction in the transaction,
+        // the runtime rejects the transaction if this instruction fails
+        let instruction = match evm_loader::account::sysvar::ed25519_instruction_data(pubkey, message, &signature.to_bytes()) {
+            Some(instruction) => instruction,
+            None => return false,
+        };
+
+        let mut ed25519_instructions = self.ed25519_instructions.borrow_mut();
+        if !ed25519_instructions.contains(&instruction) {
+            ed25519_instructions.push(instruction);
+        }
+
+        true
+    }
+
     fn clone_solana_account(&self, address: &Pubkey) -> OwnedAccountInfo {
         info!("clone_solana_account {}", address);
 
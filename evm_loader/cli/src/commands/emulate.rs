@@ -1,7 +1,7 @@
 use log::{debug, info};
 
 use evm::{H160, U256, ExitReason};
-use evm_loader::{executor::{Machine, LAMPORTS_PER_SIGNATURE}, config::{EVM_STEPS_MIN, PAYMENT_TO_TREASURE}};
+use evm_loader::{executor::{Machine, LAMPORTS_PER_SIGNATURE}, config::{EVM_CONFIG, EVM_STEPS_MIN, PAYMENT_TO_TREASURE}, transaction::TransactionType};
 
 use crate::{
     account_storage::{
@@ -25,7 +25,9 @@ pub fn execute(
     value: Option<U256>,
     token_mint: &Pubkey,
     chain_id: u64,
+    coinbase: H160,
     max_steps_to_execute: u64,
+    transaction_type: TransactionType,
 ) -> NeonCliResult {
     debug!("command_emulate(config={:?}, contract_id={:?}, caller_id={:?}, data={:?}, value={:?})",
         config,
@@ -37,7 +39,7 @@ pub fn execute(
     let syscall_stubs = Stubs::new(config)?;
     solana_sdk::program_stubs::set_syscall_stubs(syscall_stubs);
 
-    let storage = EmulatorAccountStorage::new(config, *token_mint, chain_id);
+    let storage = EmulatorAccountStorage::new(config, *token_mint, chain_id, coinbase);
 
     let program_id = if let Some(program_id) = contract_id {
         debug!("program_id to call: {}", program_id);
@@ -51,7 +53,7 @@ pub fn execute(
         program_id
     };
 
-    let (exit_reason, result, actions, steps_executed) = {
+    let (exit_reason, result, actions, steps_executed, evm_gas_used, accessed_addresses, accessed_storage) = {
         let gas_limit = U256::from(999_999_999_999_u64);
         let mut executor = Machine::new(caller_id, &storage)?;
         debug!("Executor initialized");
@@ -102,8 +104,14 @@ pub fn execute(
         debug!("Execute done, exit_reason={:?}, result={:?}", exit_reason, result);
         debug!("{} steps executed", steps_executed);
 
+        let evm_gas_used = executor.used_gas();
+        let accessed_addresses = executor.state_mut().accessed_addresses().to_vec();
+        let accessed_storage = executor.state_mut().accessed_storage()
+            .iter()
+            .map(|(address, key)| serde_json::json!({ "address": address, "key": key }))
+            .collect::<Vec<_>>();
         let actions = executor.into_state_actions();
-        (exit_reason, result, actions, steps_executed)
+        (exit_reason, result, actions, steps_executed, evm_gas_used, accessed_addresses, accessed_storage)
     };
 
     let accounts_operations = storage.calc_accounts_operations(&actions);
@@ -113,6 +121,9 @@ pub fn execute(
     let begin_end_gas = 2 * LAMPORTS_PER_SIGNATURE;
     let actions_gas = storage.apply_actions(actions);
     let accounts_gas = storage.apply_accounts_operations(accounts_operations);
+    if EVM_CONFIG.has_base_fee && transaction_type == TransactionType::DynamicFee {
+        storage.add_base_fee_accounts();
+    }
     debug!("Gas - steps: {steps_gas}, actions: {actions_gas}, accounts: {accounts_gas}");
 
     debug!("Call done");
@@ -139,6 +150,12 @@ pub fn execute(
         .cloned()
         .collect();
 
+    let ed25519_instructions: Vec<String> = storage.ed25519_instructions
+        .borrow()
+        .iter()
+        .map(hex::encode)
+        .collect();
+
     let js = serde_json::json!({
         "accounts": accounts,
         "solana_accounts": solana_accounts,
@@ -147,7 +164,11 @@ pub fn execute(
         "exit_status": status,
         "exit_reason": exit_reason,
         "steps_executed": steps_executed,
-        "used_gas": steps_gas + begin_end_gas + actions_gas + accounts_gas
+        "used_gas": steps_gas + begin_end_gas + actions_gas + accounts_gas,
+        "evm_gas_used": evm_gas_used.as_u64(),
+        "ed25519_instructions": ed25519_instructions,
+        "accessed_addresses": accessed_addresses,
+        "accessed_storage": accessed_storage
     });
 
     println!("{}", js);
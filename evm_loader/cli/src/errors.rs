@@ -117,6 +117,9 @@ pub enum NeonCliError {
     // Account nonce exceeds u64::max
     #[error("Transaction count overflow")]
     TrxCountOverflow,
+    /// RLP encoded transaction can't be decoded
+    #[error("Invalid transaction. {0:?}")]
+    InvalidTransaction(rlp::DecoderError),
 
     /// Environment Error
     #[error("Environment error {0:?}")]
@@ -173,6 +176,7 @@ impl NeonCliError {
             NeonCliError::TransactionFailed                 => 244, // => 4200,
             NeonCliError::TooManySteps                      => 245,
             NeonCliError::TrxCountOverflow                  => 246,
+            NeonCliError::InvalidTransaction(_)             => 247,
             NeonCliError::UnknownError                      => 249, // => 4900,
         }
     }
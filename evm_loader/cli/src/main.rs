@@ -32,6 +32,7 @@ use evm_loader::{
     account::{
         EthereumAccount,
     },
+    transaction::TransactionType,
 };
 
 use evm::{H160, H256, U256};
@@ -130,8 +131,12 @@ pub fn keccak256_digest(data: &[u8]) -> Vec<u8> {
 
 #[derive(Debug)]
 pub struct UnsignedTransaction {
+    pub transaction_type: TransactionType,
     pub nonce: u64,
+    /// For EIP-1559 transactions equals to `max_fee_per_gas`
     pub gas_price: U256,
+    /// Used by EIP-1559 transactions only
+    pub max_priority_fee_per_gas: U256,
     pub gas_limit: U256,
     pub to: Option<H160>,
     pub value: U256,
@@ -139,11 +144,107 @@ pub struct UnsignedTransaction {
     pub chain_id: U256,
 }
 
+impl UnsignedTransaction {
+    /// Decodes legacy RLP list or EIP-2718 typed (EIP-2930, EIP-1559) transaction.
+    /// Signature fields, if present, and access list are ignored
+    /// # Errors
+    /// Returns `Err` if the transaction is malformed or its type is unsupported
+    pub fn from_rlp(transaction: &[u8]) -> Result<Self, rlp::DecoderError> {
+        let (transaction_type, payload) = match transaction.first() {
+            Some(&byte) if byte >= 0xC0 => (TransactionType::Legacy, transaction),
+            Some(0x01) => (TransactionType::AccessList, &transaction[1..]),
+            Some(0x02) => (TransactionType::DynamicFee, &transaction[1..]),
+            Some(_) => return Err(rlp::DecoderError::Custom("Unsupported transaction type")),
+            None => return Err(rlp::DecoderError::RlpIsTooShort),
+        };
+
+        let rlp = rlp::Rlp::new(payload);
+        if !rlp.is_list() {
+            return Err(rlp::DecoderError::RlpExpectedToBeList);
+        }
+
+        let decode_to = |rlp: rlp::Rlp| -> Result<Option<H160>, rlp::DecoderError> {
+            if rlp.is_empty() { Ok(None) } else { rlp.as_val().map(Some) }
+        };
+
+        let transaction = match transaction_type {
+            TransactionType::Legacy => {
+                // EIP-155 signing payload ends with `chain_id, 0, 0`, signed transaction with `v, r, s`
+                let chain_id = if rlp.item_count()? < 9 {
+                    U256::zero()
+                } else {
+                    let v: U256 = rlp.val_at(6)?;
+                    if rlp.at(7)?.is_empty() {
+                        v
+                    } else if v >= U256::from(35) {
+                        (v - 35) / 2
+                    } else {
+                        U256::zero()
+                    }
+                };
+
+                Self {
+                    transaction_type,
+                    nonce: rlp.val_at(0)?,
+                    gas_price: rlp.val_at(1)?,
+                    max_priority_fee_per_gas: U256::zero(),
+                    gas_limit: rlp.val_at(2)?,
+                    to: decode_to(rlp.at(3)?)?,
+                    value: rlp.val_at(4)?,
+                    data: rlp.val_at(5)?,
+                    chain_id,
+                }
+            }
+            TransactionType::AccessList => Self {
+                transaction_type,
+                chain_id: rlp.val_at(0)?,
+                nonce: rlp.val_at(1)?,
+                gas_price: rlp.val_at(2)?,
+                max_priority_fee_per_gas: U256::zero(),
+                gas_limit: rlp.val_at(3)?,
+                to: decode_to(rlp.at(4)?)?,
+                value: rlp.val_at(5)?,
+                data: rlp.val_at(6)?,
+            },
+            TransactionType::DynamicFee => Self {
+                transaction_type,
+                chain_id: rlp.val_at(0)?,
+                nonce: rlp.val_at(1)?,
+                max_priority_fee_per_gas: rlp.val_at(2)?,
+                gas_price: rlp.val_at(3)?,
+                gas_limit: rlp.val_at(4)?,
+                to: decode_to(rlp.at(5)?)?,
+                value: rlp.val_at(6)?,
+                data: rlp.val_at(7)?,
+            },
+        };
+
+        Ok(transaction)
+    }
+}
+
 impl rlp::Encodable for UnsignedTransaction {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(9);
-        s.append(&self.nonce);
-        s.append(&self.gas_price);
+        match self.transaction_type {
+            TransactionType::Legacy => {
+                s.begin_list(9);
+                s.append(&self.nonce);
+                s.append(&self.gas_price);
+            }
+            TransactionType::AccessList => {
+                s.begin_list(8);
+                s.append(&self.chain_id);
+                s.append(&self.nonce);
+                s.append(&self.gas_price);
+            }
+            TransactionType::DynamicFee => {
+                s.begin_list(9);
+                s.append(&self.chain_id);
+                s.append(&self.nonce);
+                s.append(&self.max_priority_fee_per_gas);
+                s.append(&self.gas_price);
+            }
+        }
         s.append(&self.gas_limit);
         match self.to.as_ref() {
             None => s.append(&""),
@@ -151,9 +252,14 @@ impl rlp::Encodable for UnsignedTransaction {
         };
         s.append(&self.value);
         s.append(&self.data);
-        s.append(&self.chain_id);
-        s.append_empty_data();
-        s.append_empty_data();
+        if self.transaction_type == TransactionType::Legacy {
+            s.append(&self.chain_id);
+            s.append_empty_data();
+            s.append_empty_data();
+        } else {
+            // Empty access list
+            s.begin_list(0);
+        }
     }
 }
 
@@ -308,6 +414,11 @@ fn is_amount<T, U>(amount: U) -> Result<(), String>
     }
 }
 
+fn read_transaction_stdin() -> Result<UnsignedTransaction, NeonCliError> {
+    let transaction = read_stdin().unwrap_or_default();
+    UnsignedTransaction::from_rlp(&transaction).map_err(NeonCliError::InvalidTransaction)
+}
+
 fn read_stdin() -> Option<Vec<u8>>{
     let mut data = String::new();
 
@@ -457,7 +568,7 @@ fn main() {
                         .value_name("CONTRACT")
                         .takes_value(true)
                         .index(2)
-                        .required(true)
+                        .required_unless("transaction")
                         .validator(is_valid_h160_or_deploy)
                         .help("The contract that executes the transaction or 'deploy'")
                 )
@@ -470,6 +581,14 @@ fn main() {
                         .validator(is_amount::<U256, _>)
                         .help("Transaction value")
                 )
+                .arg(
+                    Arg::with_name("transaction")
+                        .long("transaction")
+                        .takes_value(false)
+                        .conflicts_with_all(&["contract", "value"])
+                        .help("Read RLP encoded legacy, EIP-2930 or EIP-1559 transaction from stdin instead of call data. \
+                               Contract, value and call data are taken from the transaction")
+                )
                 .arg(
                     Arg::with_name("token_mint")
                         .long("token_mint")
@@ -487,6 +606,15 @@ fn main() {
                         .required(false)
                         .help("Network chain_id"),
                 )
+                .arg(
+                    Arg::with_name("coinbase")
+                        .long("coinbase")
+                        .value_name("OPERATOR_ETHER")
+                        .takes_value(true)
+                        .required(false)
+                        .validator(is_valid_h160)
+                        .help("Ether address of the operator which executes the transaction (COINBASE), zero address if not set"),
+                )
                 .arg(
                     Arg::with_name("max_steps_to_execute")
                         .long("max_steps_to_execute")
@@ -717,14 +845,27 @@ fn main() {
     let result: NeonCliResult =
         match (sub_command, sub_matches) {
             ("emulate", Some(arg_matches)) => {
-                let contract = h160_or_deploy_of(arg_matches, "contract");
                 let sender = h160_of(arg_matches, "sender").unwrap();
-                let data = read_stdin();
-                let value = value_of(arg_matches, "value");
+                let transaction = if arg_matches.is_present("transaction") {
+                    Some(read_transaction_stdin().unwrap_or_else(|e| {
+                        error!("{}", e);
+                        exit(e.error_code() as i32);
+                    }))
+                } else {
+                    None
+                };
+                let (contract, data, value, transaction_type) = if let Some(transaction) = &transaction {
+                    (transaction.to, Some(transaction.data.clone()), Some(transaction.value), transaction.transaction_type)
+                } else {
+                    (h160_or_deploy_of(arg_matches, "contract"), read_stdin(), value_of(arg_matches, "value"), TransactionType::Legacy)
+                };
 
                 // Read ELF params only if token_mint or chain_id is not set.
                 let mut token_mint = pubkey_of(arg_matches, "token_mint");
-                let mut chain_id = value_of(arg_matches, "chain_id");
+                let mut chain_id = value_of(arg_matches, "chain_id").or_else(|| transaction
+                    .as_ref()
+                    .filter(|transaction| !transaction.chain_id.is_zero())
+                    .map(|transaction| transaction.chain_id.as_u64()));
                 if token_mint.is_none() || chain_id.is_none() {
                     let cached_elf_params = CachedElfParams::new(&config);
                     token_mint = token_mint.or_else(|| Some(Pubkey::from_str(
@@ -736,6 +877,7 @@ fn main() {
                 }
                 let token_mint = token_mint.unwrap();
                 let chain_id = chain_id.unwrap();
+                let coinbase = h160_of(arg_matches, "coinbase").unwrap_or_default();
                 let max_steps_to_execute = value_of::<u64>(arg_matches, "max_steps_to_execute").unwrap();
 
                 emulate::execute(&config,
@@ -745,7 +887,9 @@ fn main() {
                                  value,
                                  &token_mint,
                                  chain_id,
-                                 max_steps_to_execute)
+                                 coinbase,
+                                 max_steps_to_execute,
+                                 transaction_type)
             }
             ("create-program-address", Some(arg_matches)) => {
                 let ether = h160_of(arg_matches, "seed").unwrap();
@@ -1,7 +1,7 @@
 use solana_sdk::{
-    program_error::ProgramError, 
-    program_stubs::SyscallStubs, 
-    sysvar::rent::Rent
+    program_error::ProgramError,
+    program_stubs::SyscallStubs,
+    sysvar::{clock::Clock, epoch_schedule::EpochSchedule, rent::Rent},
 };
 
 use crate::errors::NeonCliError;
@@ -9,6 +9,8 @@ use crate::Config;
 
 pub struct Stubs {
     rent: Rent,
+    clock: Clock,
+    epoch_schedule: EpochSchedule,
 }
 
 impl Stubs {
@@ -17,7 +19,15 @@ impl Stubs {
         let data = config.rpc_client.get_account_data(&rent_pubkey)?;
         let rent = bincode::deserialize(&data).map_err(|_| ProgramError::InvalidArgument)?;
 
-        Ok(Box::new(Self { rent }))
+        let clock_pubkey = solana_sdk::sysvar::clock::id();
+        let data = config.rpc_client.get_account_data(&clock_pubkey)?;
+        let clock = bincode::deserialize(&data).map_err(|_| ProgramError::InvalidArgument)?;
+
+        let epoch_schedule_pubkey = solana_sdk::sysvar::epoch_schedule::id();
+        let data = config.rpc_client.get_account_data(&epoch_schedule_pubkey)?;
+        let epoch_schedule = bincode::deserialize(&data).map_err(|_| ProgramError::InvalidArgument)?;
+
+        Ok(Box::new(Self { rent, clock, epoch_schedule }))
     }
 }
 
@@ -31,4 +41,24 @@ impl SyscallStubs for Stubs {
 
         0
     }
+
+    fn sol_get_clock_sysvar(&self, pointer: *mut u8) -> u64 {
+        unsafe {
+            #[allow(clippy::cast_ptr_alignment)]
+            let clock = pointer.cast::<Clock>();
+            *clock = self.clock.clone();
+        }
+
+        0
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, pointer: *mut u8) -> u64 {
+        unsafe {
+            #[allow(clippy::cast_ptr_alignment)]
+            let epoch_schedule = pointer.cast::<EpochSchedule>();
+            *epoch_schedule = self.epoch_schedule;
+        }
+
+        0
+    }
 }
@@ -13,6 +13,10 @@ use syn::{
 pub struct NetSpecificConfig {
     pub chain_id: u64,
     pub operators_whitelist: Vec<String>,
+    #[serde(default)]
+    pub call_solana_allowlist: Vec<String>,
+    #[serde(default)]
+    pub call_solana_denylist: Vec<String>,
     pub token_mint: TokenMint,
 }
 
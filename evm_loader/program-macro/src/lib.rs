@@ -44,6 +44,46 @@ pub fn operators_whitelist(tokens: TokenStream) -> TokenStream {
     .into()
 }
 
+struct ProgramsListInput {
+    name: Ident,
+    _separator: Token![;],
+    list: Punctuated<LitStr, Token![,]>,
+}
+
+impl Parse for ProgramsListInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self {
+            name: input.parse()?,
+            _separator: input.parse()?,
+            list: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+#[proc_macro]
+pub fn programs_list(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ProgramsListInput);
+
+    let mut programs: Vec<Vec<u8>> = input
+        .list
+        .iter()
+        .map(LitStr::value)
+        .map(|key| bs58::decode(key).into_vec().unwrap())
+        .collect();
+
+    programs.sort_unstable();
+
+    let name = input.name;
+    let len = programs.len();
+
+    quote! {
+        pub static #name: [::solana_program::pubkey::Pubkey; #len] = [
+            #(::solana_program::pubkey::Pubkey::new_from_array([#((#programs),)*]),)*
+        ];
+    }
+    .into()
+}
+
 struct ElfParamInput {
     name: Ident,
     _separator: Token![,],
@@ -132,6 +172,8 @@ pub fn net_specific_config_parser(tokens: TokenStream) -> TokenStream {
     let NetSpecificConfig {
         chain_id,
         operators_whitelist,
+        call_solana_allowlist,
+        call_solana_denylist,
         token_mint: TokenMint {
             neon_token_mint,
             decimals,
@@ -144,6 +186,11 @@ pub fn net_specific_config_parser(tokens: TokenStream) -> TokenStream {
 
         operators_whitelist![#(#operators_whitelist),*];
 
+        /// Solana programs callable from contracts, no program is allowed if the list is empty
+        programs_list![CALL_SOLANA_ALLOWLIST; #(#call_solana_allowlist),*];
+        /// Solana programs which contracts are not allowed to call
+        programs_list![CALL_SOLANA_DENYLIST; #(#call_solana_denylist),*];
+
         /// Token Mint ID
         pub mod token_mint {
             use super::declare_param_id;
@@ -111,6 +111,22 @@ impl<'a> Neon<'a> {
 
         Ok(())
     }
+
+    /// Emits `NATIVE_TRANSFER` record: source, target and big-endian 32 bytes value of the native NEON movement.
+    /// The record type differs from `LOGn`, so it can't be confused with contract logs.
+    /// Zero `source` is used for deposits and zero `target` is used for withdrawals.
+    /// Records are emitted only if the program is built with `native-transfer-logs` feature.
+    /// Doesn't require Neon program account, because deposit instruction doesn't have it.
+    pub fn on_neon_transfer(source: H160, target: H160, value: U256) {
+        if !cfg!(feature = "native-transfer-logs") || value.is_zero() {
+            return;
+        }
+
+        let mut value_bytes = [0_u8; 32];
+        value.to_big_endian(&mut value_bytes);
+
+        sol_log_data(&[b"NATIVE_TRANSFER", source.as_bytes(), target.as_bytes(), &value_bytes]);
+    }
 }
 
 impl<'a> Deref for Neon<'a> {
@@ -270,7 +286,7 @@ pub struct Token<'a>(&'a AccountInfo<'a>);
 
 impl<'a> Token<'a> {
     pub fn from_account(info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
-        if !spl_token::check_id(info.key) {
+        if !spl_token::check_id(info.key) && !spl_token_2022::check_id(info.key) {
             return Err!(ProgramError::InvalidArgument; "Account {} - is not token program", info.key);
         }
 
@@ -284,7 +300,7 @@ impl<'a> Token<'a> {
         owner: &AccountInfo<'a>
     ) -> Result<(), ProgramError> {
         invoke(
-            &spl_token::instruction::initialize_account3(
+            &spl_token_2022::instruction::initialize_account3(
                 self.0.key,
                 account.key,
                 mint.key,
@@ -22,6 +22,8 @@ pub struct Data {
     pub slot: u64,
     /// Stored accounts length
     pub accounts_len: usize,
+    /// Part of the gas price which is burned, zero for legacy and EIP-2930 transactions
+    pub base_fee_per_gas: U256,
 }
 
 /// Storage account data for the finalized transaction state
@@ -35,7 +37,7 @@ impl Packable for Data {
     /// Storage struct tag
     const TAG: u8 = super::TAG_STATE;
     /// Storage struct serialized size
-    const SIZE: usize = 32 + 32 + 20 + 32 + 32 + 32 + 32 + 8 + 8;
+    const SIZE: usize = 32 + 32 + 20 + 32 + 32 + 32 + 32 + 8 + 8 + 32;
 
     /// Deserialize `Storage` struct from input data
     #[must_use]
@@ -52,7 +54,8 @@ impl Packable for Data {
             operator,
             slot,
             accounts_len,
-        ) = array_refs![data, 32, 32, 20, 32, 32, 32, 32, 8, 8];
+            base_fee_per_gas,
+        ) = array_refs![data, 32, 32, 20, 32, 32, 32, 32, 8, 8, 32];
 
         Self {
             owner: Pubkey::new_from_array(*owner),
@@ -64,6 +67,7 @@ impl Packable for Data {
             operator: Pubkey::new_from_array(*operator),
             slot: u64::from_le_bytes(*slot),
             accounts_len: usize::from_le_bytes(*accounts_len),
+            base_fee_per_gas: U256::from_little_endian(base_fee_per_gas),
         }
     }
 
@@ -81,7 +85,8 @@ impl Packable for Data {
             operator,
             slot,
             accounts_len,
-        ) = mut_array_refs![data, 32, 32, 20, 32, 32, 32, 32, 8, 8];
+            base_fee_per_gas,
+        ) = mut_array_refs![data, 32, 32, 20, 32, 32, 32, 32, 8, 8, 32];
 
         owner.copy_from_slice(self.owner.as_ref());
         hash.copy_from_slice(&self.transaction_hash);
@@ -92,6 +97,7 @@ impl Packable for Data {
         operator.copy_from_slice(self.operator.as_ref());
         *slot = self.slot.to_le_bytes();
         *accounts_len = self.accounts_len.to_le_bytes();
+        self.base_fee_per_gas.to_little_endian(base_fee_per_gas);
     }
 }
 
@@ -1,5 +1,18 @@
+use std::convert::TryInto;
+
+use arrayref::array_ref;
+use evm::H256;
 use solana_program::account_info::AccountInfo;
+use solana_program::ed25519_program;
 use solana_program::program_error::ProgramError;
+use solana_program::sysvar::instructions::load_instruction_at_checked;
+
+/// Size of `SlotHashes` entry: slot (u64) and hash (32 bytes)
+const SLOT_HASH_ENTRY_SIZE: usize = 8 + 32;
+/// Size of `Ed25519SignatureOffsets`: seven u16 fields
+const ED25519_OFFSETS_SIZE: usize = 7 * 2;
+/// Ed25519 program refers to the data of its own instruction by this index
+const ED25519_CURRENT_INSTRUCTION: usize = 0xFFFF;
 
 
 pub struct Instructions<'a> {
@@ -14,4 +27,123 @@ impl<'a> Instructions<'a> {
 
         Ok(Self { info })
     }
+
+    /// Checks that the transaction contains an instruction of the native Ed25519 program
+    /// with this signature. The runtime rejects the whole transaction if the signature is invalid.
+    /// Only signatures with the data stored inside the Ed25519 instruction itself are recognized.
+    #[must_use]
+    pub fn is_ed25519_verified(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        let count = {
+            let data = self.info.data.borrow();
+            if data.len() < 2 {
+                return false;
+            }
+
+            u16::from_le_bytes(*array_ref![data, 0, 2])
+        };
+
+        (0..usize::from(count))
+            .filter_map(|index| load_instruction_at_checked(index, self.info).ok())
+            .filter(|instruction| ed25519_program::check_id(&instruction.program_id))
+            .any(|instruction| is_ed25519_signature_in(&instruction.data, pubkey, message, signature))
+    }
+}
+
+/// Data of the Ed25519 program instruction which verifies a single signature,
+/// the layout is recognized by `Instructions::is_ed25519_verified`.
+/// Returns `None` if the message doesn't fit into the instruction offsets.
+#[must_use]
+pub fn ed25519_instruction_data(pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> Option<Vec<u8>> {
+    let pubkey_offset = 2 + ED25519_OFFSETS_SIZE;
+    let signature_offset = pubkey_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let offsets = [
+        signature_offset, ED25519_CURRENT_INSTRUCTION,
+        pubkey_offset, ED25519_CURRENT_INSTRUCTION,
+        message_offset, message.len(), ED25519_CURRENT_INSTRUCTION,
+    ];
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for field in offsets {
+        let field = u16::try_from(field).ok()?;
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(pubkey);
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Some(data)
+}
+
+/// Searches the signature in the Ed25519 program instruction data.
+/// Data layout: `u8` signatures count, `u8` padding, followed by `Ed25519SignatureOffsets` entries.
+fn is_ed25519_signature_in(data: &[u8], pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let count = match data.first() {
+        Some(&count) => usize::from(count),
+        None => return false,
+    };
+
+    let entries = data.get(2..).unwrap_or_default();
+    entries.chunks_exact(ED25519_OFFSETS_SIZE).take(count).any(|offsets| {
+        let field = |index: usize| usize::from(u16::from_le_bytes(*array_ref![offsets, index * 2, 2]));
+
+        let (signature_offset, signature_instruction) = (field(0), field(1));
+        let (pubkey_offset, pubkey_instruction) = (field(2), field(3));
+        let (message_offset, message_size, message_instruction) = (field(4), field(5), field(6));
+
+        if (signature_instruction != ED25519_CURRENT_INSTRUCTION)
+            || (pubkey_instruction != ED25519_CURRENT_INSTRUCTION)
+            || (message_instruction != ED25519_CURRENT_INSTRUCTION)
+        {
+            return false;
+        }
+
+        (data.get(signature_offset..signature_offset + 64) == Some(&signature[..]))
+            && (data.get(pubkey_offset..pubkey_offset + 32) == Some(&pubkey[..]))
+            && (data.get(message_offset..message_offset + message_size) == Some(message))
+    })
+}
+
+
+/// Returns the hash of the most recent slot from the serialized `SlotHashes` sysvar data.
+#[must_use]
+pub fn latest_slot_hash(data: &[u8]) -> Option<H256> {
+    if data.len() < 8 + SLOT_HASH_ENTRY_SIZE {
+        return None;
+    }
+
+    let len = u64::from_le_bytes(*array_ref![data, 0, 8]);
+    if len == 0 {
+        return None;
+    }
+
+    Some(H256::from_slice(&data[8 + 8..8 + SLOT_HASH_ENTRY_SIZE]))
+}
+
+/// Finds the hash of the slot in the serialized `SlotHashes` sysvar data.
+/// Data layout: `u64` entries count, followed by `(slot: u64, hash: [u8; 32])` entries, newest first.
+#[must_use]
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Option<H256> {
+    if data.len() < 8 {
+        return None;
+    }
+
+    let len = u64::from_le_bytes(*array_ref![data, 0, 8]);
+    let len: usize = len.try_into().ok()?;
+
+    let entries = data[8..].chunks_exact(SLOT_HASH_ENTRY_SIZE).take(len);
+    for entry in entries {
+        let entry_slot = u64::from_le_bytes(*array_ref![entry, 0, 8]);
+        if entry_slot == slot {
+            return Some(H256::from_slice(&entry[8..]));
+        }
+
+        if entry_slot < slot {
+            break; // entries are sorted by slot in descending order
+        }
+    }
+
+    None
 }
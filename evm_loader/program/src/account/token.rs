@@ -1,27 +1,28 @@
 use solana_program::account_info::AccountInfo;
 use solana_program::program_error::ProgramError;
-use solana_program::program_pack::{IsInitialized, Pack};
+use spl_token_2022::extension::{BaseState, StateWithExtensions};
 use std::ops::Deref;
 
-pub struct Account<'a, T: Pack + IsInitialized> {
+/// SPL Token or Token-2022 account, extensions are ignored
+pub struct Account<'a, T: BaseState> {
     pub info: &'a AccountInfo<'a>,
     data: T,
 }
 
-impl<'a, T: Pack + IsInitialized> Account<'a, T> {
+impl<'a, T: BaseState> Account<'a, T> {
     pub fn from_account(info: &'a AccountInfo<'a>) -> Result<Self, ProgramError> {
-        if !spl_token::check_id(info.owner) {
+        if !spl_token::check_id(info.owner) && !spl_token_2022::check_id(info.owner) {
             return Err!(ProgramError::InvalidArgument; "Account {} - is not spl token owned", info.key);
         }
 
         let data = info.try_borrow_data()?;
-        let data = T::unpack(&data)?;
+        let data = StateWithExtensions::<T>::unpack(&data)?.base;
 
         Ok(Self { info, data })
     }
 }
 
-impl<'a, T: Pack + IsInitialized> Deref for Account<'a, T> {
+impl<'a, T: BaseState> Deref for Account<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -29,5 +30,5 @@ impl<'a, T: Pack + IsInitialized> Deref for Account<'a, T> {
     }
 }
 
-pub type State<'a> = Account<'a, spl_token::state::Account>;
-pub type Mint<'a> = Account<'a, spl_token::state::Mint>;
\ No newline at end of file
+pub type State<'a> = Account<'a, spl_token_2022::state::Account>;
+pub type Mint<'a> = Account<'a, spl_token_2022::state::Mint>;
\ No newline at end of file
@@ -4,8 +4,9 @@ use std::convert::TryInto;
 use evm::{H160, U256};
 use solana_program::entrypoint::{MAX_PERMITTED_DATA_INCREASE, ProgramResult};
 use solana_program::instruction::Instruction;
-use solana_program::program::{invoke, invoke_signed_unchecked};
+use solana_program::program::{invoke, invoke_signed, invoke_signed_unchecked};
 use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::system_instruction;
 use solana_program::sysvar::Sysvar;
@@ -16,23 +17,37 @@ use crate::config::STORAGE_ENTRIES_IN_CONTRACT_ACCOUNT;
 use crate::executor::{AccountMeta, Action};
 
 impl<'a> ProgramAccountStorage<'a> {
+    /// Burns `base_fee` and transfers `value` from `origin` to `operator`.
+    /// `base_fee` must be a whole number of NEON token units, see `split_gas_cost`
     pub fn transfer_gas_payment(
         &mut self,
         origin: H160,
         mut operator: EthereumAccount<'a>,
         value: U256,
+        base_fee: U256,
     ) -> ProgramResult {
         let origin_balance = self.balance(&origin);
-        if origin_balance < value {
+        if origin_balance < value.saturating_add(base_fee) {
             return Err!(ProgramError::InsufficientFunds; "Account {} - insufficient funds", origin);
         }
 
+        if !base_fee.is_zero() {
+            self.burn_from_deposit_pool(base_fee)?;
+
+            let origin_account = self.ethereum_account_mut(&origin);
+            // balance checked above
+            origin_account.balance -= base_fee;
+
+            program::Neon::on_neon_transfer(origin, H160::zero(), base_fee);
+        }
+
         if operator.address == origin {
             return Ok(())
         }
 
-        if self.ethereum_accounts.contains_key(&operator.address) {
-            self.transfer_neon_tokens(&origin, &operator.address, value)?;
+        let operator_address = operator.address;
+        if self.ethereum_accounts.contains_key(&operator_address) {
+            self.transfer_neon_tokens(&origin, &operator_address, value)?;
             core::mem::drop(operator);
         } else {
             let origin_account = self.ethereum_account_mut(&origin);
@@ -42,9 +57,57 @@ impl<'a> ProgramAccountStorage<'a> {
             operator.balance += value;
         }
 
+        program::Neon::on_neon_transfer(origin, operator_address, value);
+
         Ok(())
     }
 
+    /// Burns NEON tokens backing `value` from the deposit pool,
+    /// so the pool balance stays equal to the NEON supply in Ethereum accounts.
+    /// Pool, mint, pool authority and token program accounts must be passed to the transaction.
+    fn burn_from_deposit_pool(&self, value: U256) -> ProgramResult {
+        let account = |key: &Pubkey| {
+            self.solana_account(key)
+                .ok_or_else(|| E!(ProgramError::NotEnoughAccountKeys; "Account {} - required to burn the base fee", key))
+        };
+
+        let mint = account(&crate::config::token_mint::id())?;
+        let token_program = account(mint.owner)?;
+
+        let (authority, bump_seed) = Pubkey::find_program_address(&[b"Deposit"], self.program_id());
+        let pool = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &authority,
+            mint.key,
+            token_program.key,
+        );
+
+        assert!(crate::config::token_mint::decimals() <= 18);
+        let additional_decimals: u32 = (18 - crate::config::token_mint::decimals()).into();
+        let amount = value / U256::from(10_u64.pow(additional_decimals));
+        if amount > U256::from(u64::MAX) {
+            return Err!(ProgramError::InvalidArgument; "Base fee {} is too big", value);
+        }
+
+        let instruction = spl_token_2022::instruction::burn_checked(
+            token_program.key,
+            &pool,
+            mint.key,
+            &authority,
+            &[],
+            amount.as_u64(),
+            crate::config::token_mint::decimals(),
+        )?;
+
+        let accounts_info = [
+            account(&pool)?.clone(),
+            mint.clone(),
+            account(&authority)?.clone(),
+            token_program.clone(),
+        ];
+
+        invoke_signed(&instruction, &accounts_info, &[&[b"Deposit", &[bump_seed]]])
+    }
+
     pub fn apply_state_change(
         &mut self,
         neon_program: &program::Neon<'a>,
@@ -80,6 +143,7 @@ impl<'a> ProgramAccountStorage<'a> {
             match action {
                 Action::NeonTransfer { source, target, value } => {
                     self.transfer_neon_tokens(&source, &target, value)?;
+                    program::Neon::on_neon_transfer(source, target, value);
                 }
                 Action::NeonWithdraw { source, value } => {
                     let account = self.ethereum_account_mut(&source);
@@ -88,6 +152,7 @@ impl<'a> ProgramAccountStorage<'a> {
                     }
 
                     account.balance -= value;
+                    program::Neon::on_neon_transfer(source, H160::zero(), value);
                 }
                 Action::EvmLog { address, topics, data } => {
                     neon_program.on_event(address, &topics, &data)?;
@@ -229,6 +294,9 @@ impl<'a> ProgramAccountStorage<'a> {
 
         assert_eq!(account.balance, U256::zero()); // balance should be moved by executor
         account.trx_count = 0;
+
+        // Storage accounts allocated for the deleted contract (including the ones
+        // allocated in this transaction) are orphaned by the new generation
         account.generation = account.generation.checked_add(1)
             .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "Account {} - generation overflow", address))?;
 
@@ -1,10 +1,9 @@
-use std::convert::TryInto;
 use evm::{H160, H256, U256};
 use solana_program::{
     pubkey::Pubkey,
-    sysvar::recent_blockhashes
+    sysvar::{instructions, slot_hashes}
 };
-use crate::account::{EthereumAccount};
+use crate::account::{EthereumAccount, sysvar::Instructions};
 use crate::account_storage::{AccountStorage, ProgramAccountStorage};
 use crate::config::STORAGE_ENTRIES_IN_CONTRACT_ACCOUNT;
 use crate::executor::{OwnedAccountInfo, OwnedAccountInfoPartial};
@@ -31,19 +30,41 @@ impl<'a> AccountStorage for ProgramAccountStorage<'a> {
     }
 
     fn block_hash(&self, number: U256) -> H256 {
-        if let Some(account) = self.solana_accounts.get(&recent_blockhashes::ID) {
-            let slot_hash_data = account.data.borrow();
-            let clock_slot = self.clock.slot;
-            if number >= clock_slot.into() {
-                return H256::default();
-            }
-            let offset: usize = (8 + (clock_slot - 1 - number.as_u64()) * 40).try_into().unwrap();
-            if offset + 32 > slot_hash_data.len() {
-                return H256::default();
-            }
-            return H256::from_slice(&slot_hash_data[offset..][..32]);
+        if number >= self.clock.slot.into() {
+            return H256::default();
         }
-        panic!("Trying to get blockhash info without providing sysvar account: {}", recent_blockhashes::ID);
+
+        if let Some(account) = self.solana_accounts.get(&slot_hashes::ID) {
+            let slot_hashes_data = account.data.borrow();
+            return crate::account::sysvar::find_slot_hash(&slot_hashes_data, number.as_u64())
+                .unwrap_or_default();
+        }
+        panic!("Trying to get blockhash info without providing sysvar account: {}", slot_hashes::ID);
+    }
+
+    fn block_coinbase(&self) -> H160 {
+        self.coinbase
+    }
+
+    fn block_prevrandao(&self) -> H256 {
+        if let Some(account) = self.solana_accounts.get(&slot_hashes::ID) {
+            let slot_hashes_data = account.data.borrow();
+            return crate::account::sysvar::latest_slot_hash(&slot_hashes_data)
+                .unwrap_or_default();
+        }
+
+        // Same value as for the empty SlotHashes, the result doesn't depend on the passed accounts otherwise
+        debug_print!("block_prevrandao: sysvar account {} is not provided", slot_hashes::ID);
+        H256::default()
+    }
+
+    fn ed25519_verify(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        if let Some(account) = self.solana_accounts.get(&instructions::ID) {
+            return Instructions { info: *account }.is_ed25519_verified(pubkey, message, signature);
+        }
+
+        debug_print!("ed25519_verify: sysvar account {} is not provided", instructions::ID);
+        false
     }
 
     fn exists(&self, address: &H160) -> bool {
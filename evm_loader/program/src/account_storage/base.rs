@@ -15,6 +15,7 @@ impl<'a> ProgramAccountStorage<'a> {
     pub fn new(
         program_id: &'a Pubkey,
         operator: &Operator<'a>,
+        coinbase: H160,
         system_program: Option<&program::System<'a>>,
         accounts: &'a [AccountInfo<'a>]
     ) -> Result<Self, ProgramError> {
@@ -61,6 +62,7 @@ impl<'a> ProgramAccountStorage<'a> {
         Ok(Self {
             program_id,
             operator: operator.key,
+            coinbase,
             clock: Clock::get()?,
             solana_accounts,
             ethereum_accounts,
@@ -34,6 +34,7 @@ pub enum AccountsReadiness {
 pub struct ProgramAccountStorage<'a> {
     program_id: &'a Pubkey,
     operator: &'a Pubkey,
+    coinbase: H160,
     clock: Clock,
 
     solana_accounts: BTreeMap<&'a Pubkey, &'a AccountInfo<'a>>,
@@ -63,8 +64,15 @@ pub trait AccountStorage {
     fn block_timestamp(&self) -> U256;
     /// Get block hash
     fn block_hash(&self, number: U256) -> H256;
+    /// Get block beneficiary: Neon address of the operator
+    fn block_coinbase(&self) -> H160;
+    /// Get block randomness: hash of the most recent slot
+    fn block_prevrandao(&self) -> H256;
     /// Get chain id
     fn chain_id(&self) -> u64;
+    /// Check that the message is signed by the Ed25519 key.
+    /// The signature is accepted only if it is verified by the Ed25519 program instruction of the same transaction.
+    fn ed25519_verify(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool;
 
     /// Check if ethereum account exists
     fn exists(&self, address: &H160) -> bool;
@@ -6,7 +6,7 @@ use const_format::formatcp;
 use evm::U256;
 use evm_loader_macro::{
     common_config_parser, declare_param_id, elf_config_parser, neon_elf_param,
-    net_specific_config_parser, operators_whitelist,
+    net_specific_config_parser, operators_whitelist, programs_list,
 };
 
 cfg_if! {
@@ -35,6 +35,109 @@ cfg_if! {
 
 elf_config_parser!("config/elf_params.toml");
 
+/// EVM hard fork rules
+pub struct EvmConfig {
+    /// Newly created contracts start with nonce 1 (EIP-161)
+    pub create_increase_nonce: bool,
+    /// Maximum size of deployed contract code (EIP-170)
+    pub create_contract_limit: Option<usize>,
+    /// Maximum size of contract init code (EIP-3860)
+    pub max_initcode_size: Option<usize>,
+    /// Reject deployment of code starting with 0xEF byte (EIP-3541)
+    pub reject_ef_code: bool,
+    /// Access list transactions (EIP-2930) and access gas costs (EIP-2929)
+    pub has_access_list: bool,
+    /// Dynamic fee transactions (EIP-1559) and BASEFEE opcode (EIP-3198)
+    pub has_base_fee: bool,
+    /// PUSH0 opcode (EIP-3855)
+    pub has_push0: bool,
+    /// TLOAD and TSTORE opcodes (EIP-1153)
+    pub has_transient_storage: bool,
+    /// SELFDESTRUCT deletes only accounts created in the same transaction (EIP-6780)
+    pub selfdestruct_only_created: bool,
+    /// Max share of used gas that can be refunded (EIP-3529)
+    pub max_refund_quotient: u64,
+    /// Refund for clearing a storage slot (EIP-3529)
+    pub sstore_clears_refund: u64,
+    /// Refund for SELFDESTRUCT (removed by EIP-3529)
+    pub selfdestruct_refund: u64,
+}
+
+const ISTANBUL: EvmConfig = EvmConfig {
+    create_increase_nonce: true,
+    create_contract_limit: Some(0x6000),
+    max_initcode_size: None,
+    reject_ef_code: false,
+    has_access_list: false,
+    has_base_fee: false,
+    has_push0: false,
+    has_transient_storage: false,
+    selfdestruct_only_created: false,
+    max_refund_quotient: 2,
+    sstore_clears_refund: 15_000,
+    selfdestruct_refund: 24_000,
+};
+
+const BERLIN: EvmConfig = EvmConfig {
+    has_access_list: true,
+    ..ISTANBUL
+};
+
+const LONDON: EvmConfig = EvmConfig {
+    reject_ef_code: true,
+    has_base_fee: true,
+    max_refund_quotient: 5,
+    sstore_clears_refund: 4_800,
+    selfdestruct_refund: 0,
+    ..BERLIN
+};
+
+const SHANGHAI: EvmConfig = EvmConfig {
+    max_initcode_size: Some(2 * 0x6000),
+    has_push0: true,
+    ..LONDON
+};
+
+/// Not a complete Cancun fork: only EIP-1153 and EIP-6780 on top of Shanghai
+const SHANGHAI_EIP1153_EIP6780: EvmConfig = EvmConfig {
+    has_transient_storage: true,
+    selfdestruct_only_created: true,
+    ..SHANGHAI
+};
+
+#[cfg(any(
+    all(feature = "istanbul", feature = "berlin"),
+    all(feature = "istanbul", feature = "london"),
+    all(feature = "istanbul", feature = "shanghai"),
+    all(feature = "istanbul", feature = "shanghai-eip1153-eip6780"),
+    all(feature = "berlin", feature = "london"),
+    all(feature = "berlin", feature = "shanghai"),
+    all(feature = "berlin", feature = "shanghai-eip1153-eip6780"),
+    all(feature = "london", feature = "shanghai"),
+    all(feature = "london", feature = "shanghai-eip1153-eip6780"),
+    all(feature = "shanghai", feature = "shanghai-eip1153-eip6780"),
+))]
+compile_error!("Only one EVM hard fork feature can be enabled");
+
+cfg_if! {
+    if #[cfg(feature = "istanbul")] {
+        pub const EVM_CONFIG: EvmConfig = ISTANBUL;
+        neon_elf_param!( NEON_EVM_FORK, "istanbul");
+    } else if #[cfg(feature = "berlin")] {
+        pub const EVM_CONFIG: EvmConfig = BERLIN;
+        neon_elf_param!( NEON_EVM_FORK, "berlin");
+    } else if #[cfg(feature = "london")] {
+        pub const EVM_CONFIG: EvmConfig = LONDON;
+        neon_elf_param!( NEON_EVM_FORK, "london");
+    } else if #[cfg(feature = "shanghai-eip1153-eip6780")] {
+        pub const EVM_CONFIG: EvmConfig = SHANGHAI_EIP1153_EIP6780;
+        neon_elf_param!( NEON_EVM_FORK, "shanghai+eip1153+eip6780");
+    } else {
+        pub const EVM_CONFIG: EvmConfig = SHANGHAI;
+        neon_elf_param!( NEON_EVM_FORK, "shanghai");
+    }
+}
+
 /// Chain ID
 #[must_use]
 pub fn chain_id() -> U256 {
@@ -54,6 +54,9 @@ fn process_instruction<'a>(
         EvmInstruction::DepositV03 => {
             instruction::neon_tokens_deposit::process(program_id, accounts, instruction)
         }
+        EvmInstruction::DepositV04 => {
+            instruction::neon_tokens_deposit::process_v04(program_id, accounts, instruction)
+        }
         EvmInstruction::Cancel => {
             instruction::transaction_cancel::process(program_id, accounts, instruction)
         }
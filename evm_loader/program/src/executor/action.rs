@@ -43,4 +43,4 @@ pub enum Action {
     EvmSelfDestruct {
         address: H160,
     },
-}
\ No newline at end of file
+}
\ No newline at end of file
@@ -1,8 +1,8 @@
-use std::{collections::BTreeMap, rc::Rc, cell::RefCell};
+use std::{collections::{BTreeMap, BTreeSet}, rc::Rc, cell::RefCell};
 
 use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program::{pubkey::Pubkey, account_info::AccountInfo};
-use evm::{U256};
+use evm::{H160, H256, U256};
 
 use crate::account_storage::AccountStorage;
 
@@ -118,12 +118,35 @@ impl AccountMeta {
 
 
 
+/// Result of the queued external instructions simulation.
+/// It is extended by the newly queued instructions and restarted when the queued instructions are reverted.
+#[derive(Default)]
+pub struct Simulation {
+    pub accounts: BTreeMap<Pubkey, OwnedAccountInfo>,
+    /// Accounts changed by the programs which can't be emulated
+    pub unknown: BTreeSet<Pubkey>,
+    pub return_data: Vec<u8>,
+    /// Number of the simulated actions
+    pub actions_len: usize,
+    /// Accounts writable in the queued instructions
+    pub written: BTreeSet<Pubkey>,
+    /// Number of the actions added to `written`
+    pub written_actions_len: usize,
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Cache {
     pub solana_accounts: BTreeMap<Pubkey, OwnedAccountInfo>,
     pub solana_accounts_partial: BTreeMap<Pubkey, OwnedAccountInfoPartial>,
     pub block_number: U256,
     pub block_timestamp: U256,
+    /// Operator of the first iteration, doesn't change between iterations
+    pub block_coinbase: H160,
+    /// Loaded on first use, doesn't change between iterations
+    pub block_prevrandao: Option<H256>,
+    /// Not saved between iterations, restarted on the first use
+    #[borsh_skip]
+    pub simulation: Simulation,
 }
 
 impl Cache {
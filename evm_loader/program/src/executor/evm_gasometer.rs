@@ -0,0 +1,693 @@
+//! EVM gas schedule, tracked per call frame.
+//! Works alongside lamport-denominated `Gasometer`,
+//! provides `GAS` opcode results, 63/64 gas forwarding and `OutOfGas` exits.
+
+use borsh::{BorshSerialize, BorshDeserialize};
+use evm::{ExitError, ExitReason, Opcode, H160, U256};
+
+use crate::account_storage::AccountStorage;
+use crate::config::EVM_CONFIG;
+use crate::precompile::is_precompile_address;
+use crate::transaction::AccessList;
+
+use super::state::ExecutorState;
+use super::handler::{OPCODE_BASEFEE, OPCODE_PUSH0, OPCODE_TLOAD, OPCODE_TSTORE};
+
+const GAS_TRANSACTION: u64 = 21_000;
+const GAS_TRANSACTION_CREATE: u64 = 32_000;
+const GAS_TRANSACTION_ZERO_DATA: u64 = 4;
+const GAS_TRANSACTION_NON_ZERO_DATA: u64 = 16;
+const GAS_ACCESS_LIST_ADDRESS: u64 = 2_400;
+const GAS_ACCESS_LIST_STORAGE_KEY: u64 = 1_900;
+
+const GAS_BASE: u64 = 2;
+const GAS_VERY_LOW: u64 = 3;
+const GAS_LOW: u64 = 5;
+const GAS_MID: u64 = 8;
+const GAS_HIGH: u64 = 10;
+const GAS_JUMPDEST: u64 = 1;
+const GAS_EXP: u64 = 10;
+const GAS_EXP_BYTE: u64 = 50;
+const GAS_SHA3: u64 = 30;
+const GAS_SHA3_WORD: u64 = 6;
+const GAS_COPY_WORD: u64 = 3;
+const GAS_EXTERNAL_ACCOUNT: u64 = 700;
+const GAS_BLOCKHASH: u64 = 20;
+const GAS_SLOAD: u64 = 800;
+const GAS_SSTORE_SET: u64 = 20_000;
+const GAS_SSTORE_RESET: u64 = 5_000;
+const GAS_SSTORE_SENTRY: u64 = 2_300;
+const GAS_WARM_STORAGE_READ: u64 = 100;
+const GAS_COLD_SLOAD: u64 = 2_100;
+const GAS_COLD_ACCOUNT_ACCESS: u64 = 2_600;
+const GAS_LOG: u64 = 375;
+const GAS_LOG_TOPIC: u64 = 375;
+const GAS_LOG_DATA: u64 = 8;
+const GAS_CREATE: u64 = 32_000;
+const GAS_INITCODE_WORD: u64 = 2;
+const GAS_CODE_DEPOSIT: u64 = 200;
+const GAS_CALL: u64 = 700;
+const GAS_CALL_VALUE: u64 = 9_000;
+const GAS_CALL_STIPEND: u64 = 2_300;
+const GAS_NEW_ACCOUNT: u64 = 25_000;
+const GAS_SELFDESTRUCT: u64 = 5_000;
+const GAS_MEMORY_WORD: u64 = 3;
+const GAS_MEMORY_QUAD_DIVISOR: u64 = 512;
+
+
+/// Memory offsets above this limit can't be paid with any realistic gas limit
+const MEMORY_LIMIT: u64 = 0xFFFF_FFFF;
+
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Frame {
+    gas_limit: u64,
+    used_gas: u64,
+    refunded_gas: i64,
+    memory_words: u64,
+}
+
+impl Frame {
+    fn new(gas_limit: u64) -> Self {
+        Self { gas_limit, used_gas: 0, refunded_gas: 0, memory_words: 0 }
+    }
+
+    fn gas_left(&self) -> u64 {
+        self.gas_limit - self.used_gas
+    }
+}
+
+/// Stack of gas counters, one per call or create frame.
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+pub struct EvmGasometer {
+    frames: Vec<Frame>,
+}
+
+/// Calculates transaction intrinsic gas: base cost, call data cost and init code cost
+#[must_use]
+pub fn intrinsic_gas(data: &[u8], is_create: bool) -> u64 {
+    let zero_bytes = data.iter().filter(|b| **b == 0).count() as u64;
+    let non_zero_bytes = data.len() as u64 - zero_bytes;
+
+    let base = if is_create {
+        GAS_TRANSACTION + GAS_TRANSACTION_CREATE + initcode_cost(data.len() as u64)
+    } else {
+        GAS_TRANSACTION
+    };
+
+    base + zero_bytes * GAS_TRANSACTION_ZERO_DATA + non_zero_bytes * GAS_TRANSACTION_NON_ZERO_DATA
+}
+
+/// EIP-3860: init code is charged per word
+fn initcode_cost(len: u64) -> u64 {
+    if EVM_CONFIG.max_initcode_size.is_some() {
+        GAS_INITCODE_WORD * words(len)
+    } else {
+        0
+    }
+}
+
+/// EIP-2930: access list is charged per address and per storage key
+#[must_use]
+pub fn access_list_gas(access_list: &AccessList) -> u64 {
+    access_list.iter()
+        .map(|(_, keys)| GAS_ACCESS_LIST_ADDRESS + GAS_ACCESS_LIST_STORAGE_KEY * keys.len() as u64)
+        .sum()
+}
+
+fn refund(value: u64) -> i64 {
+    i64::try_from(value).expect("refund fits i64")
+}
+
+fn peek(stack: &evm::Stack, index: usize) -> Result<U256, ExitError> {
+    let value = stack.peek(index)?;
+    Ok(U256::from_big_endian(value.as_bytes()))
+}
+
+fn peek_address(stack: &evm::Stack, index: usize) -> Result<H160, ExitError> {
+    let value = stack.peek(index)?;
+    Ok(H160::from(value))
+}
+
+fn words(len: u64) -> u64 {
+    (len + 31) / 32
+}
+
+fn memory_cost(words: u64) -> u64 {
+    GAS_MEMORY_WORD * words + (words * words) / GAS_MEMORY_QUAD_DIVISOR
+}
+
+fn copy_len(len: U256) -> Result<u64, ExitError> {
+    if len > U256::from(MEMORY_LIMIT) {
+        return Err(ExitError::OutOfGas);
+    }
+
+    Ok(len.as_u64())
+}
+
+fn is_empty_account<B: AccountStorage>(state: &ExecutorState<B>, address: &H160) -> bool {
+    state.nonce(address).is_zero() && state.balance(address).is_zero() && state.code_size(address).is_zero()
+}
+
+/// EIP-2929: account access cost depends on whether the account is warm.
+/// Before Berlin every account access costs the same.
+fn account_access_cost<B: AccountStorage>(state: &mut ExecutorState<B>, address: H160) -> u64 {
+    let is_warm = state.access_address(address) || is_precompile_address(&address);
+
+    if !EVM_CONFIG.has_access_list {
+        GAS_EXTERNAL_ACCOUNT
+    } else if is_warm {
+        GAS_WARM_STORAGE_READ
+    } else {
+        GAS_COLD_ACCOUNT_ACCESS
+    }
+}
+
+/// EIP-2929: cold storage slot surcharge
+fn storage_access_cost<B: AccountStorage>(state: &mut ExecutorState<B>, address: H160, key: U256) -> u64 {
+    let is_warm = state.access_storage(address, key);
+
+    if EVM_CONFIG.has_access_list && !is_warm { GAS_COLD_SLOAD } else { 0 }
+}
+
+/// Base cost of CALL family opcodes
+fn call_cost<B: AccountStorage>(state: &mut ExecutorState<B>, target: H160) -> u64 {
+    let cost = account_access_cost(state, target);
+    if EVM_CONFIG.has_access_list { cost } else { GAS_CALL }
+}
+
+/// Cost of reading warm storage slot
+fn sload_cost() -> u64 {
+    if EVM_CONFIG.has_access_list { GAS_WARM_STORAGE_READ } else { GAS_SLOAD }
+}
+
+impl EvmGasometer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { frames: Vec::new() }
+    }
+
+    /// Starts the transaction frame and charges intrinsic gas
+    pub fn begin(&mut self, gas_limit: U256, intrinsic_gas: u64) -> Result<(), ExitError> {
+        let gas_limit = if gas_limit > U256::from(u64::MAX) { u64::MAX } else { gas_limit.as_u64() };
+
+        self.frames.push(Frame::new(gas_limit));
+        self.record_cost(intrinsic_gas)
+    }
+
+    /// Gas available in the current frame
+    #[must_use]
+    pub fn gas_left(&self) -> u64 {
+        self.frames.last().map_or(0, Frame::gas_left)
+    }
+
+    /// Gas used by the transaction, accounting refunds
+    #[must_use]
+    pub fn used_gas(&self) -> u64 {
+        let frame = match self.frames.first() {
+            Some(frame) => frame,
+            None => return 0,
+        };
+
+        let refund = u64::try_from(frame.refunded_gas).unwrap_or(0);
+        let refund = std::cmp::min(refund, frame.used_gas / EVM_CONFIG.max_refund_quotient);
+
+        frame.used_gas - refund
+    }
+
+    pub fn record_cost(&mut self, cost: u64) -> Result<(), ExitError> {
+        let frame = self.frames.last_mut().ok_or(ExitError::OutOfGas)?;
+
+        if cost > frame.gas_left() {
+            frame.used_gas = frame.gas_limit;
+            return Err(ExitError::OutOfGas);
+        }
+
+        frame.used_gas += cost;
+        Ok(())
+    }
+
+    fn record_refund(&mut self, refund: i64) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.refunded_gas = frame.refunded_gas.saturating_add(refund);
+        }
+    }
+
+    fn record_memory(&mut self, offset: U256, len: U256) -> Result<(), ExitError> {
+        if len.is_zero() {
+            return Ok(());
+        }
+
+        let end = offset.checked_add(len).ok_or(ExitError::OutOfGas)?;
+        if end > U256::from(MEMORY_LIMIT) {
+            return Err(ExitError::OutOfGas);
+        }
+
+        let new_words = words(end.as_u64());
+        let old_words = self.frames.last().map_or(0, |f| f.memory_words);
+        if new_words <= old_words {
+            return Ok(());
+        }
+
+        self.record_cost(memory_cost(new_words) - memory_cost(old_words))?;
+
+        if let Some(frame) = self.frames.last_mut() {
+            frame.memory_words = new_words;
+        }
+
+        Ok(())
+    }
+
+    /// Charges contract code deposit cost in the frame of the creating init code
+    pub fn record_code_deposit(&mut self, len: usize) -> Result<(), ExitError> {
+        self.record_cost(GAS_CODE_DEPOSIT * len as u64)
+    }
+
+    /// Takes gas for a nested call or create: all but one 64th of the gas left,
+    /// limited by `target_gas` if specified
+    pub fn forward_gas(&mut self, target_gas: Option<u64>) -> u64 {
+        let gas_left = self.gas_left();
+        let available = gas_left - gas_left / 64;
+        let gas = target_gas.map_or(available, |target| std::cmp::min(target, available));
+
+        if let Some(frame) = self.frames.last_mut() {
+            frame.used_gas += gas;
+        }
+
+        gas
+    }
+
+    /// Charges a precompile call: the forwarded gas pays `cost`, the rest returns to the caller.
+    /// If the forwarded gas is not enough, all of it is consumed
+    pub fn record_precompile(&mut self, target_gas: Option<u64>, stipend: u64, cost: u64) -> Result<(), ExitError> {
+        let gas_limit = self.forward_gas(target_gas) + stipend;
+        if cost > gas_limit {
+            return Err(ExitError::OutOfGas);
+        }
+
+        if let Some(frame) = self.frames.last_mut() {
+            frame.used_gas = frame.used_gas.saturating_sub(gas_limit - cost);
+        }
+
+        Ok(())
+    }
+
+    /// Stipend given to the callee when value is transferred
+    #[must_use]
+    pub fn call_stipend(value: U256) -> u64 {
+        if value.is_zero() { 0 } else { GAS_CALL_STIPEND }
+    }
+
+    /// Starts gas counting for a nested call or create
+    pub fn enter(&mut self, gas_limit: u64) {
+        self.frames.push(Frame::new(gas_limit));
+    }
+
+    /// Finishes current frame.
+    /// Unused gas returns to the caller unless execution failed with an error,
+    /// refunds are kept only if execution succeeded.
+    pub fn exit(&mut self, reason: &ExitReason) {
+        if self.frames.len() < 2 {
+            if let Some(frame) = self.frames.last_mut() {
+                if !reason.is_succeed() {
+                    frame.refunded_gas = 0;
+                }
+                if matches!(reason, ExitReason::Error(_) | ExitReason::Fatal(_)) {
+                    frame.used_gas = frame.gas_limit;
+                }
+            }
+            return;
+        }
+
+        let child = self.frames.pop().expect("frames is not empty");
+        let parent = self.frames.last_mut().expect("frames is not empty");
+
+        match reason {
+            ExitReason::Succeed(_) => {
+                parent.used_gas = parent.used_gas.saturating_sub(child.gas_left());
+                parent.refunded_gas = parent.refunded_gas.saturating_add(child.refunded_gas);
+            }
+            ExitReason::Revert(_) => {
+                parent.used_gas = parent.used_gas.saturating_sub(child.gas_left());
+            }
+            _ => {}
+        }
+    }
+
+    /// Charges the gas cost of the opcode before it is executed
+    #[allow(clippy::too_many_lines)]
+    pub fn record_opcode<B: AccountStorage>(
+        &mut self,
+        state: &mut ExecutorState<B>,
+        context: &evm::Context,
+        opcode: Opcode,
+        stack: &evm::Stack,
+    ) -> Result<(), ExitError> {
+        let cost = match opcode {
+            Opcode::STOP | Opcode::RETURN | Opcode::REVERT | Opcode::INVALID => 0,
+
+            Opcode::ADDRESS | Opcode::ORIGIN | Opcode::CALLER | Opcode::CALLVALUE | Opcode::CALLDATASIZE
+            | Opcode::CODESIZE | Opcode::GASPRICE | Opcode::COINBASE | Opcode::TIMESTAMP | Opcode::NUMBER
+            | Opcode::DIFFICULTY | Opcode::GASLIMIT | Opcode::RETURNDATASIZE | Opcode::POP | Opcode::PC
+            | Opcode::MSIZE | Opcode::GAS | Opcode::CHAINID | OPCODE_BASEFEE | OPCODE_PUSH0 => GAS_BASE,
+
+            Opcode::ADD | Opcode::SUB | Opcode::NOT | Opcode::LT | Opcode::GT | Opcode::SLT | Opcode::SGT
+            | Opcode::EQ | Opcode::ISZERO | Opcode::AND | Opcode::OR | Opcode::XOR | Opcode::BYTE
+            | Opcode::SHL | Opcode::SHR | Opcode::SAR | Opcode::CALLDATALOAD => GAS_VERY_LOW,
+
+            Opcode::MUL | Opcode::DIV | Opcode::SDIV | Opcode::MOD | Opcode::SMOD | Opcode::SIGNEXTEND
+            | Opcode::SELFBALANCE => GAS_LOW,
+
+            Opcode::ADDMOD | Opcode::MULMOD | Opcode::JUMP => GAS_MID,
+            Opcode::JUMPI => GAS_HIGH,
+            Opcode::JUMPDEST => GAS_JUMPDEST,
+            Opcode::BLOCKHASH => GAS_BLOCKHASH,
+
+            Opcode::BALANCE | Opcode::EXTCODESIZE | Opcode::EXTCODEHASH => {
+                account_access_cost(state, peek_address(stack, 0)?)
+            }
+
+            Opcode::EXP => {
+                let exponent = peek(stack, 1)?;
+                let exponent_bytes = (exponent.bits() as u64 + 7) / 8;
+                GAS_EXP + GAS_EXP_BYTE * exponent_bytes
+            }
+
+            Opcode::SHA3 => {
+                let len = peek(stack, 1)?;
+                self.record_memory(peek(stack, 0)?, len)?;
+                GAS_SHA3 + GAS_SHA3_WORD * words(copy_len(len)?)
+            }
+
+            Opcode::MLOAD | Opcode::MSTORE => {
+                self.record_memory(peek(stack, 0)?, U256::from(32))?;
+                GAS_VERY_LOW
+            }
+
+            Opcode::MSTORE8 => {
+                self.record_memory(peek(stack, 0)?, U256::one())?;
+                GAS_VERY_LOW
+            }
+
+            Opcode::CALLDATACOPY | Opcode::CODECOPY | Opcode::RETURNDATACOPY => {
+                let len = peek(stack, 2)?;
+                self.record_memory(peek(stack, 0)?, len)?;
+                GAS_VERY_LOW + GAS_COPY_WORD * words(copy_len(len)?)
+            }
+
+            Opcode::EXTCODECOPY => {
+                let len = peek(stack, 3)?;
+                self.record_memory(peek(stack, 1)?, len)?;
+                account_access_cost(state, peek_address(stack, 0)?) + GAS_COPY_WORD * words(copy_len(len)?)
+            }
+
+            OPCODE_TLOAD | OPCODE_TSTORE => GAS_WARM_STORAGE_READ,
+
+            Opcode::SLOAD => {
+                let key = peek(stack, 0)?;
+                match storage_access_cost(state, context.address, key) {
+                    0 => sload_cost(),
+                    cold_sload => cold_sload,
+                }
+            }
+
+            Opcode::SSTORE => {
+                if self.gas_left() <= GAS_SSTORE_SENTRY {
+                    return Err(ExitError::OutOfGas);
+                }
+
+                let key = peek(stack, 0)?;
+                let new = peek(stack, 1)?;
+
+                storage_access_cost(state, context.address, key) + self.sstore_cost(state, &context.address, &key, &new)
+            }
+
+            Opcode::LOG0 | Opcode::LOG1 | Opcode::LOG2 | Opcode::LOG3 | Opcode::LOG4 => {
+                let topics = u64::from(opcode.as_u8() - Opcode::LOG0.as_u8());
+                let len = peek(stack, 1)?;
+                self.record_memory(peek(stack, 0)?, len)?;
+                GAS_LOG + GAS_LOG_TOPIC * topics + GAS_LOG_DATA * copy_len(len)?
+            }
+
+            Opcode::CREATE => {
+                let len = peek(stack, 2)?;
+                self.record_memory(peek(stack, 1)?, len)?;
+                GAS_CREATE + initcode_cost(copy_len(len)?)
+            }
+
+            Opcode::CREATE2 => {
+                let len = peek(stack, 2)?;
+                self.record_memory(peek(stack, 1)?, len)?;
+                GAS_CREATE + GAS_SHA3_WORD * words(copy_len(len)?) + initcode_cost(copy_len(len)?)
+            }
+
+            Opcode::CALL | Opcode::CALLCODE => {
+                self.record_memory(peek(stack, 3)?, peek(stack, 4)?)?;
+                self.record_memory(peek(stack, 5)?, peek(stack, 6)?)?;
+
+                let target = peek_address(stack, 1)?;
+                let value = peek(stack, 2)?;
+
+                let mut cost = call_cost(state, target);
+                if !value.is_zero() {
+                    cost += GAS_CALL_VALUE;
+
+                    if (opcode == Opcode::CALL) && is_empty_account(state, &target) {
+                        cost += GAS_NEW_ACCOUNT;
+                    }
+                }
+
+                cost
+            }
+
+            Opcode::DELEGATECALL | Opcode::STATICCALL => {
+                self.record_memory(peek(stack, 2)?, peek(stack, 3)?)?;
+                self.record_memory(peek(stack, 4)?, peek(stack, 5)?)?;
+                call_cost(state, peek_address(stack, 1)?)
+            }
+
+            Opcode::SUICIDE => {
+                let target = peek_address(stack, 0)?;
+
+                let mut cost = GAS_SELFDESTRUCT;
+                let is_warm = state.access_address(target);
+                if EVM_CONFIG.has_access_list && !is_warm {
+                    cost += GAS_COLD_ACCOUNT_ACCESS;
+                }
+                if !state.balance(&context.address).is_zero() && is_empty_account(state, &target) {
+                    cost += GAS_NEW_ACCOUNT;
+                }
+
+                self.record_refund(refund(EVM_CONFIG.selfdestruct_refund));
+                cost
+            }
+
+            _ => {
+                let byte = opcode.as_u8();
+                if (Opcode::PUSH1.as_u8()..=Opcode::SWAP16.as_u8()).contains(&byte) {
+                    GAS_VERY_LOW // PUSH1-PUSH32, DUP1-DUP16, SWAP1-SWAP16
+                } else {
+                    0
+                }
+            }
+        };
+
+        self.record_cost(cost)
+    }
+
+    /// EIP-2200 storage write cost, updates refund counter.
+    /// Since Berlin SLOAD and reset costs exclude cold access surcharge (EIP-2929).
+    fn sstore_cost<B: AccountStorage>(&mut self, state: &ExecutorState<B>, address: &H160, key: &U256, new: &U256) -> u64 {
+        let sload = sload_cost();
+        let reset = if EVM_CONFIG.has_access_list { GAS_SSTORE_RESET - GAS_COLD_SLOAD } else { GAS_SSTORE_RESET };
+
+        let current = state.storage(address, key);
+        if &current == new {
+            return sload;
+        }
+
+        let original = state.backend.storage(address, key);
+        if original == current {
+            if original.is_zero() {
+                return GAS_SSTORE_SET;
+            }
+
+            if new.is_zero() {
+                self.record_refund(refund(EVM_CONFIG.sstore_clears_refund));
+            }
+
+            return reset;
+        }
+
+        if !original.is_zero() {
+            if current.is_zero() {
+                self.record_refund(-refund(EVM_CONFIG.sstore_clears_refund));
+            } else if new.is_zero() {
+                self.record_refund(refund(EVM_CONFIG.sstore_clears_refund));
+            }
+        }
+
+        if &original == new {
+            let value = if original.is_zero() { GAS_SSTORE_SET - sload } else { reset - sload };
+            self.record_refund(refund(value));
+        }
+
+        sload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evm::{ExitRevert, ExitSucceed};
+
+    fn start(gas_limit: u64) -> EvmGasometer {
+        let mut gasometer = EvmGasometer::new();
+        gasometer.begin(U256::from(gas_limit), 0).unwrap();
+        gasometer
+    }
+
+    #[test]
+    fn intrinsic_gas_cost() {
+        assert_eq!(intrinsic_gas(&[], false), GAS_TRANSACTION);
+        assert_eq!(intrinsic_gas(&[0, 0, 1], false), GAS_TRANSACTION + 2 * 4 + 16);
+
+        let init_code = [1_u8; 33];
+        assert_eq!(
+            intrinsic_gas(&init_code, true),
+            GAS_TRANSACTION + GAS_TRANSACTION_CREATE + 33 * 16 + initcode_cost(33)
+        );
+    }
+
+    #[test]
+    fn access_list_cost() {
+        let access_list = vec![
+            (H160::repeat_byte(0x11), vec![U256::zero(), U256::one()]),
+            (H160::repeat_byte(0x22), vec![]),
+        ];
+
+        assert_eq!(access_list_gas(&AccessList::new()), 0);
+        assert_eq!(access_list_gas(&access_list), 2 * 2_400 + 2 * 1_900);
+    }
+
+    #[test]
+    fn begin_charges_intrinsic_gas() {
+        let mut gasometer = EvmGasometer::new();
+        assert_eq!(gasometer.begin(U256::from(20_000), 21_000), Err(ExitError::OutOfGas));
+
+        let mut gasometer = EvmGasometer::new();
+        gasometer.begin(U256::MAX, 21_000).unwrap();
+        assert_eq!(gasometer.gas_left(), u64::MAX - 21_000);
+    }
+
+    #[test]
+    fn record_cost_out_of_gas_consumes_all_gas() {
+        let mut gasometer = start(100);
+        gasometer.record_cost(60).unwrap();
+        assert_eq!(gasometer.gas_left(), 40);
+
+        assert_eq!(gasometer.record_cost(41), Err(ExitError::OutOfGas));
+        assert_eq!(gasometer.gas_left(), 0);
+        assert_eq!(gasometer.used_gas(), 100);
+    }
+
+    #[test]
+    fn memory_expansion_cost() {
+        assert_eq!(memory_cost(1), 3);
+        assert_eq!(memory_cost(32), 3 * 32 + 2);
+
+        let mut gasometer = start(1_000_000);
+        gasometer.record_memory(U256::zero(), U256::from(32)).unwrap();
+        assert_eq!(gasometer.used_gas(), 3);
+
+        // Memory is already expanded
+        gasometer.record_memory(U256::from(10), U256::from(10)).unwrap();
+        assert_eq!(gasometer.used_gas(), 3);
+
+        // Only the expansion is charged
+        gasometer.record_memory(U256::zero(), U256::from(1024)).unwrap();
+        assert_eq!(gasometer.used_gas(), memory_cost(32));
+
+        assert_eq!(gasometer.record_memory(U256::MAX, U256::one()), Err(ExitError::OutOfGas));
+        assert_eq!(gasometer.record_memory(U256::from(MEMORY_LIMIT), U256::one()), Err(ExitError::OutOfGas));
+    }
+
+    #[test]
+    fn forward_all_but_one_64th() {
+        let mut gasometer = start(6_400);
+        assert_eq!(gasometer.forward_gas(None), 6_300);
+        assert_eq!(gasometer.gas_left(), 100);
+
+        let mut gasometer = start(6_400);
+        assert_eq!(gasometer.forward_gas(Some(1_000)), 1_000);
+        assert_eq!(gasometer.gas_left(), 5_400);
+    }
+
+    #[test]
+    fn child_frame_returns_unused_gas() {
+        let mut gasometer = start(10_000);
+
+        let gas = gasometer.forward_gas(Some(5_000));
+        gasometer.enter(gas);
+        gasometer.record_cost(1_000).unwrap();
+        gasometer.exit(&ExitReason::Succeed(ExitSucceed::Stopped));
+        assert_eq!(gasometer.gas_left(), 9_000);
+
+        let gas = gasometer.forward_gas(Some(5_000));
+        gasometer.enter(gas);
+        gasometer.record_cost(1_000).unwrap();
+        gasometer.exit(&ExitReason::Revert(ExitRevert::Reverted));
+        assert_eq!(gasometer.gas_left(), 8_000);
+
+        let gas = gasometer.forward_gas(Some(5_000));
+        gasometer.enter(gas);
+        gasometer.record_cost(1_000).unwrap();
+        gasometer.exit(&ExitReason::Error(ExitError::InvalidCode));
+        assert_eq!(gasometer.gas_left(), 3_000);
+    }
+
+    #[test]
+    fn refunds_are_kept_only_on_success() {
+        let mut gasometer = start(100_000);
+        gasometer.record_cost(50_000).unwrap();
+
+        let gas = gasometer.forward_gas(Some(10_000));
+        gasometer.enter(gas);
+        gasometer.record_refund(1_000);
+        gasometer.exit(&ExitReason::Revert(ExitRevert::Reverted));
+        assert_eq!(gasometer.used_gas(), 50_000);
+
+        let gas = gasometer.forward_gas(Some(10_000));
+        gasometer.enter(gas);
+        gasometer.record_refund(1_000);
+        gasometer.exit(&ExitReason::Succeed(ExitSucceed::Stopped));
+        assert_eq!(gasometer.used_gas(), 50_000 - 1_000);
+
+        // Refund is capped by a quotient of used gas
+        gasometer.record_refund(1_000_000);
+        gasometer.exit(&ExitReason::Succeed(ExitSucceed::Stopped));
+        assert_eq!(gasometer.used_gas(), 50_000 - 50_000 / EVM_CONFIG.max_refund_quotient);
+
+        gasometer.exit(&ExitReason::Revert(ExitRevert::Reverted));
+        assert_eq!(gasometer.used_gas(), 50_000);
+    }
+
+    #[test]
+    fn precompile_charges_only_its_cost() {
+        let mut gasometer = start(64_000);
+        gasometer.record_precompile(Some(10_000), 0, 3_000).unwrap();
+        assert_eq!(gasometer.used_gas(), 3_000);
+
+        gasometer.record_precompile(None, 0, 100).unwrap();
+        assert_eq!(gasometer.used_gas(), 3_100);
+
+        assert_eq!(gasometer.record_precompile(Some(1_000), 0, 3_000), Err(ExitError::OutOfGas));
+        assert_eq!(gasometer.used_gas(), 4_100);
+    }
+
+    #[test]
+    fn call_stipend_on_value_transfer() {
+        assert_eq!(EvmGasometer::call_stipend(U256::zero()), 0);
+        assert_eq!(EvmGasometer::call_stipend(U256::one()), GAS_CALL_STIPEND);
+    }
+}
@@ -1,11 +1,23 @@
-use evm::{H160, ExitReason, U256, Transfer, ExitError, CONFIG, Handler, H256, Capture};
+use evm::{H160, ExitReason, U256, Transfer, ExitError, Handler, H256, Capture};
 use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
 
 use crate::{
-    event, account_storage::AccountStorage, precompile::{call_precompile, is_precompile_address}
+    event, account_storage::AccountStorage,
+    precompile::{call_precompile, is_precompile_address, is_erc20_for_spl_address, precompile_gas},
+    config::{EVM_CONFIG, BASE_FEE_PER_GAS, BLOCK_GAS_LIMIT},
 };
 
-use super::{state::ExecutorState};
+use super::{state::ExecutorState, evm_gasometer::EvmGasometer};
+
+
+/// EIP-3198: BASEFEE opcode
+pub const OPCODE_BASEFEE: evm::Opcode = evm::Opcode(0x48);
+/// EIP-3855: PUSH0 opcode
+pub const OPCODE_PUSH0: evm::Opcode = evm::Opcode(0x5F);
+/// EIP-1153: TLOAD opcode
+pub const OPCODE_TLOAD: evm::Opcode = evm::Opcode(0x5C);
+/// EIP-1153: TSTORE opcode
+pub const OPCODE_TSTORE: evm::Opcode = evm::Opcode(0x5D);
 
 
 pub struct CallInterrupt {
@@ -14,6 +26,7 @@ pub struct CallInterrupt {
     pub code_address: H160,
     pub input: Vec<u8>,
     pub is_static: bool,
+    pub gas_limit: u64,
 }
 
 pub struct CreateInterrupt {
@@ -21,6 +34,7 @@ pub struct CreateInterrupt {
     pub transfer: Option<evm::Transfer>,
     pub address: H160,
     pub init_code: Vec<u8>,
+    pub gas_limit: u64,
 }
 
 
@@ -30,6 +44,9 @@ pub struct Executor<'a, B: AccountStorage> {
     pub origin: H160,
     pub gas_limit: U256,
     pub gas_price: U256,
+    pub gasometer: EvmGasometer,
+    /// Address of the currently executed contract, updated before each opcode
+    pub context_address: H160,
 }
 
 
@@ -67,11 +84,18 @@ impl<'a, B: AccountStorage> Executor<'a, B> {
     ) -> ProgramResult {
         event!(TransactCall { caller, address, value, data, gas_limit });
 
+        if is_erc20_for_spl_address(&origin) {
+            return Err!(ProgramError::InvalidArgument; "Origin {} is in the reserved ERC20ForSpl range", origin);
+        }
+
         self.gas_limit = gas_limit;
         self.gas_price = gas_price;
 
         self.state.inc_nonce(origin);
         self.state.enter(false);
+
+        self.state.access_address(origin);
+        self.state.access_address(address);
         
         if let Err(error) = self.state.transfer(origin, address, value)  {
             return Err!(ProgramError::InsufficientFunds; "ExitError={:?}", error);
@@ -83,18 +107,31 @@ impl<'a, B: AccountStorage> Executor<'a, B> {
     pub fn create_begin(
         &mut self,
         origin: H160,
-        _init_code: &[u8],
+        init_code: &[u8],
         value: U256,
         gas_limit: U256,
         gas_price: U256
     ) -> Result<H160, ProgramError> {
         event!(TransactCreate { caller, value, init_code, gas_limit });
 
+        if let Some(limit) = EVM_CONFIG.max_initcode_size {
+            if init_code.len() > limit {
+                return Err!(ProgramError::InvalidInstructionData; "Init code size {} exceeds limit {}", init_code.len(), limit);
+            }
+        }
+
+        if is_erc20_for_spl_address(&origin) {
+            return Err!(ProgramError::InvalidArgument; "Origin {} is in the reserved ERC20ForSpl range", origin);
+        }
+
         self.gas_limit = gas_limit;
         self.gas_price = gas_price;
 
         let scheme = evm::CreateScheme::Legacy { caller: origin };
         let address = self.create_address(scheme);
+        if is_erc20_for_spl_address(&address) {
+            return Err!(ProgramError::InvalidArgument; "Deploy to the reserved ERC20ForSpl range {} is not allowed", address);
+        }
         
         if self.code_size(address) > U256::zero() {
             return Err!(ProgramError::AccountAlreadyInitialized; "Attempt to deploy to existing account (code_size > 0)")
@@ -107,7 +144,11 @@ impl<'a, B: AccountStorage> Executor<'a, B> {
         self.state.inc_nonce(origin);
         self.state.enter(false);
 
-        if CONFIG.create_increase_nonce {
+        self.state.access_address(origin);
+        self.state.access_address(address);
+        self.state.set_created(address);
+
+        if EVM_CONFIG.create_increase_nonce {
             self.state.inc_nonce(address);
         }
 
@@ -163,7 +204,7 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
     }
 
     fn gas_left(&self) -> U256 {
-        self.gas_limit
+        U256::from(self.gasometer.gas_left())
     }
 
     fn gas_price(&self) -> U256 {
@@ -183,19 +224,21 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
     }
 
     fn block_coinbase(&self) -> H160 {
-        H160::default()
+        self.state.block_coinbase()
     }
 
     fn block_timestamp(&self) -> U256 {
         self.state.block_timestamp()
     }
 
+    /// PREVRANDAO (EIP-4399)
     fn block_difficulty(&self) -> U256 {
-        U256::zero()
+        let prevrandao = self.state.block_prevrandao();
+        U256::from_big_endian(prevrandao.as_bytes())
     }
 
     fn block_gas_limit(&self) -> U256 {
-        U256::max_value()
+        U256::from(BLOCK_GAS_LIMIT)
     }
 
     fn chain_id(&self) -> U256 {
@@ -229,7 +272,11 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
         let balance = self.balance(address);
 
         self.state.transfer(address, target, balance)?;
-        self.state.set_deleted(address);
+
+        // EIP-6780: accounts created before the transaction keep code and storage
+        if !EVM_CONFIG.selfdestruct_only_created || self.state.is_created(&address) {
+            self.state.set_deleted(address);
+        }
 
         Ok(())
     }
@@ -248,12 +295,21 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
             return Capture::Exit((ExitError::StaticModeViolation.into(), None, Vec::new()))
         }
 
+        if let Some(limit) = EVM_CONFIG.max_initcode_size {
+            if init_code.len() > limit {
+                return Capture::Exit((ExitError::CreateContractLimit.into(), None, Vec::new()))
+            }
+        }
+
         if !value.is_zero() && (self.balance(caller) < value) {
             return Capture::Exit((ExitError::OutOfFund.into(), None, Vec::new()))
         }
 
         // Get the create address from given scheme.
         let address = self.create_address(scheme);
+        if is_erc20_for_spl_address(&address) {
+            return Capture::Exit((ExitError::CreateCollision.into(), None, Vec::new()))
+        }
 
         event!(Create {
             caller,
@@ -266,6 +322,9 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
 
 
         self.state.inc_nonce(caller);
+        self.state.access_address(address);
+
+        let gas_limit = self.gasometer.forward_gas(None);
 
         if self.state.code_size(&address) > U256::zero() {
             return Capture::Exit((ExitError::CreateCollision.into(), None, Vec::new()))
@@ -283,7 +342,7 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
 
         let transfer = Some(evm::Transfer { source: caller, target: address, value });
 
-        Capture::Trap(CreateInterrupt{context, transfer, address, init_code})
+        Capture::Trap(CreateInterrupt{context, transfer, address, init_code, gas_limit})
     }
 
     fn call(
@@ -291,7 +350,7 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
         code_address: H160,
         transfer: Option<evm::Transfer>,
         input: Vec<u8>,
-        #[allow(unused_variables)] target_gas: Option<u64>,
+        target_gas: Option<u64>,
         is_static: bool,
         context: evm::Context,
     ) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
@@ -316,20 +375,70 @@ impl<'a, B: AccountStorage> Handler for Executor<'a, B> {
             }
         }
 
+        let stipend = transfer.as_ref().map_or(0, |t| EvmGasometer::call_stipend(t.value));
+
+        if let Some(cost) = precompile_gas(&code_address, &input) {
+            if let Err(error) = self.gasometer.record_precompile(target_gas, stipend, cost) {
+                return Capture::Exit((error.into(), Vec::new()))
+            }
+        }
+
         let precompile_result = call_precompile(code_address, &input, &context, &mut self.state);
         if let Some(Capture::Exit(exit_value)) = precompile_result {
             return Capture::Exit(exit_value);
         }
 
-        Capture::Trap(CallInterrupt{context, transfer, code_address, input, is_static})
+        let gas_limit = self.gasometer.forward_gas(target_gas) + stipend;
+
+        Capture::Trap(CallInterrupt{context, transfer, code_address, input, is_static, gas_limit})
     }
 
     fn pre_validate(
         &mut self,
-        _context: &evm::Context,
-        _opcode: evm::Opcode,
-        _stack: &evm::Stack,
+        context: &evm::Context,
+        opcode: evm::Opcode,
+        stack: &evm::Stack,
     ) -> Result<(), ExitError> {
-        Ok(())
+        self.context_address = context.address;
+
+        self.gasometer.record_opcode(&mut self.state, context, opcode, stack)
+    }
+
+    fn other(&mut self, opcode: evm::Opcode, machine: &mut evm::Machine) -> Result<(), ExitError> {
+        match opcode {
+            OPCODE_PUSH0 if EVM_CONFIG.has_push0 => {
+                machine.stack_mut().push(H256::zero())
+            }
+            OPCODE_BASEFEE if EVM_CONFIG.has_base_fee => {
+                let mut base_fee = H256::zero();
+                U256::from(BASE_FEE_PER_GAS).to_big_endian(base_fee.as_bytes_mut());
+
+                machine.stack_mut().push(base_fee)
+            }
+            OPCODE_TLOAD if EVM_CONFIG.has_transient_storage => {
+                let key = machine.stack_mut().pop()?;
+                let key = U256::from_big_endian(key.as_bytes());
+
+                let mut value = H256::zero();
+                self.state.transient_storage(&self.context_address, &key).to_big_endian(value.as_bytes_mut());
+
+                machine.stack_mut().push(value)
+            }
+            OPCODE_TSTORE if EVM_CONFIG.has_transient_storage => {
+                if self.state.is_static_context() {
+                    return Err(ExitError::StaticModeViolation);
+                }
+
+                let key = machine.stack_mut().pop()?;
+                let value = machine.stack_mut().pop()?;
+
+                let key = U256::from_big_endian(key.as_bytes());
+                let value = U256::from_big_endian(value.as_bytes());
+
+                self.state.set_transient_storage(self.context_address, key, value);
+                Ok(())
+            }
+            _ => Err(ExitError::DesignatedInvalid)
+        }
     }
 }
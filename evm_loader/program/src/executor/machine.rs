@@ -1,15 +1,18 @@
 use borsh::{BorshSerialize, BorshDeserialize};
-use evm::{H160, U256, ExitReason, Capture, ExitFatal, Resolve, CONFIG, Control, ExitError, Handler};
+use evm::{H160, U256, ExitReason, Capture, ExitFatal, Resolve, Control, ExitError, Handler};
 use solana_program::{program_error::ProgramError, entrypoint::ProgramResult};
 
 use crate::{
     emit_exit,
-    account_storage::AccountStorage
+    account_storage::AccountStorage,
+    config::EVM_CONFIG,
+    transaction::AccessList,
 };
 
 use super::{
     handler::{CallInterrupt, CreateInterrupt, Executor}, 
-    state::ExecutorState, action::Action
+    state::ExecutorState, action::Action,
+    evm_gasometer::{EvmGasometer, intrinsic_gas, access_list_gas},
 };
 
 /// Represents reason of an Ethereum transaction.
@@ -46,7 +49,9 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
 
         let executor = Executor { 
             origin, state, 
-            gas_limit: U256::zero(), gas_price: U256::zero() 
+            gas_limit: U256::zero(), gas_price: U256::zero(),
+            gasometer: EvmGasometer::new(),
+            context_address: H160::zero(),
         };
         Ok(Self { executor, runtime: Vec::new(), steps_executed: 0 })
     }
@@ -61,6 +66,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
 
         self.runtime.serialize(&mut &mut buffer).unwrap();
         self.executor.state.serialize(&mut &mut buffer).unwrap();
+        self.executor.gasometer.serialize(&mut &mut buffer).unwrap();
     }
 
     /// Deserializes and restores state of runtime and executor from a storage account.
@@ -69,12 +75,15 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
 
         let runtime = BorshDeserialize::deserialize(&mut buffer).unwrap();
         let state = ExecutorState::deserialize(&mut buffer, backend).unwrap();
+        let gasometer = BorshDeserialize::deserialize(&mut buffer).unwrap();
 
         let executor = Executor { 
             origin: storage.caller,
             state,
             gas_limit: storage.gas_limit,
             gas_price: storage.gas_price,
+            gasometer,
+            context_address: H160::zero(),
         };
 
         Ok(Self { executor, runtime, steps_executed: 0 })
@@ -86,6 +95,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
     ///
     /// May return following errors:
     /// - `InsufficientFunds` if the caller lacks funds for the operation
+    /// - `InvalidArgument` if the gas limit doesn't cover intrinsic gas
     pub fn call_begin(
         &mut self,
         caller: H160,
@@ -98,6 +108,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         debug_print!("call_begin");
 
         self.executor.call_begin(caller, code_address, &input, transfer_value, gas_limit, gas_price)?;
+        self.begin_gas(gas_limit, intrinsic_gas(&input, false))?;
 
         let code = self.executor.code(code_address);
         let valids = self.executor.valids(code_address);
@@ -116,6 +127,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
     ///
     /// May return following errors:
     /// - `InsufficientFunds` if the caller lacks funds for the operation
+    /// - `InvalidArgument` if the gas limit doesn't cover intrinsic gas
     pub fn create_begin(
         &mut self,
         caller: H160,
@@ -127,6 +139,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         debug_print!("create_begin");
 
         let address = self.executor.create_begin(caller, &init_code, transfer_value, gas_limit, gas_price)?;
+        self.begin_gas(gas_limit, intrinsic_gas(&init_code, true))?;
 
         let valids = evm::Valids::compute(&init_code);
         let context = evm::Context{ address, caller, apparent_value: transfer_value };
@@ -138,6 +151,36 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         Ok(())
     }
 
+    /// Pre-warms addresses and storage slots of the transaction access list (EIP-2930)
+    /// and charges it as a part of intrinsic gas.
+    ///
+    /// # Errors
+    ///
+    /// May return `InvalidArgument` if the gas limit doesn't cover the access list
+    pub fn apply_access_list(&mut self, access_list: &AccessList) -> ProgramResult {
+        let gas = access_list_gas(access_list);
+        if self.executor.gasometer.record_cost(gas).is_err() {
+            return Err!(ProgramError::InvalidArgument; "Access list gas {} exceeds gas limit", gas);
+        }
+
+        for (address, keys) in access_list {
+            self.executor.state.access_address(*address);
+            for key in keys {
+                self.executor.state.access_storage(*address, *key);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn begin_gas(&mut self, gas_limit: U256, intrinsic_gas: u64) -> ProgramResult {
+        if self.executor.gasometer.begin(gas_limit, intrinsic_gas).is_err() {
+            return Err!(ProgramError::InvalidArgument; "Intrinsic gas {} exceeds gas limit {}", intrinsic_gas, gas_limit);
+        }
+
+        Ok(())
+    }
+
     #[inline]
     fn process_capture(
         capture: Capture<ExitReason, Resolve<Executor<B>>>,
@@ -196,6 +239,7 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         let valids = self.executor.valids(interrupt.code_address);
 
         self.executor.state.enter(interrupt.is_static);
+        self.executor.gasometer.enter(interrupt.gas_limit);
 
         if let Some(transfer) = interrupt.transfer {
             self.executor.transfer(transfer).map_err(|e| (Vec::new(), e.into()))?;
@@ -214,8 +258,10 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
 
     fn apply_create(&mut self, interrupt: CreateInterrupt) -> Result<(), (Vec<u8>, ExitReason)> {
         self.executor.state.enter( false);
+        self.executor.gasometer.enter(interrupt.gas_limit);
+        self.executor.state.set_created(interrupt.address);
 
-        if CONFIG.create_increase_nonce {
+        if EVM_CONFIG.create_increase_nonce {
             self.executor.state.inc_nonce(interrupt.address);
         }
 
@@ -239,7 +285,9 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         if reason.is_succeed() {
             self.executor.state.exit_commit();
         }
-        
+
+        self.executor.gasometer.exit(&reason);
+
         let return_value = exited_runtime.machine().return_value();
         if self.runtime.is_empty() {
             return Err((return_value, reason));
@@ -257,19 +305,32 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
     fn apply_exit_create(&mut self, exited_runtime: &evm::Runtime, mut reason: ExitReason, address: H160) -> Result<(), (Vec<u8>, ExitReason)> {
 
         if reason.is_succeed() {
-            match CONFIG.create_contract_limit {
-                Some(limit) if exited_runtime.machine().return_value_len() > limit => {
+            let return_value = exited_runtime.machine().return_value();
+
+            match EVM_CONFIG.create_contract_limit {
+                Some(limit) if return_value.len() > limit => {
                     self.executor.state.exit_revert();
                     reason = ExitError::CreateContractLimit.into();
                 },
+                _ if EVM_CONFIG.reject_ef_code && (return_value.first() == Some(&0xEF)) => {
+                    // EIP-3541: 0xEF prefix is reserved for EVM Object Format
+                    self.executor.state.exit_revert();
+                    reason = ExitError::DesignatedInvalid.into();
+                },
                 _ => {
-                    self.executor.state.exit_commit();
-                    let return_value = exited_runtime.machine().return_value();
-                    self.executor.state.set_code(address, return_value);
+                    if let Err(error) = self.executor.gasometer.record_code_deposit(return_value.len()) {
+                        self.executor.state.exit_revert();
+                        reason = error.into();
+                    } else {
+                        self.executor.state.exit_commit();
+                        self.executor.state.set_code(address, return_value);
+                    }
                 }
             };
         }
 
+        self.executor.gasometer.exit(&reason);
+
         let runtime = match self.runtime.last_mut() {
             Some((runtime, _)) => runtime,
             None => return match reason {
@@ -346,6 +407,12 @@ impl<'a, B: AccountStorage> Machine<'a, B> {
         Ok(())
     }
 
+    /// Returns EVM gas used by the transaction.
+    #[must_use]
+    pub fn used_gas(&self) -> U256 {
+        U256::from(self.executor.gasometer.used_gas())
+    }
+
     /// Returns number of executed steps.
     #[must_use]
     pub fn get_steps_executed(&self) -> u64 {
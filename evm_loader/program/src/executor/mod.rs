@@ -4,6 +4,7 @@ mod state;
 mod handler;
 mod machine;
 mod gasometer;
+mod evm_gasometer;
 
 pub use cache::OwnedAccountInfo;
 pub use cache::OwnedAccountInfoPartial;
@@ -11,4 +12,5 @@ pub use cache::AccountMeta;
 pub use action::Action;
 pub use state::ExecutorState;
 pub use gasometer::{Gasometer, LAMPORTS_PER_SIGNATURE};
+pub use evm_gasometer::EvmGasometer;
 pub use machine::Machine;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use evm::{H160, U256, H256, ExitError, ExitReason};
 use solana_program::instruction::Instruction;
@@ -12,16 +12,42 @@ use crate::executor::cache::AccountMeta;
 
 use super::{OwnedAccountInfo, OwnedAccountInfoPartial};
 use super::action::Action;
-use super::cache::Cache;
+use super::cache::{Cache, Simulation};
 
 
+/// Lengths of the state journals on entering a call or create.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Snapshot {
+    actions_len: usize,
+    accessed_addresses_len: usize,
+    accessed_storage_len: usize,
+    created_len: usize,
+    transient_storage_len: usize,
+}
+
 /// Represents the state of executor abstracted away from a self.backend.
 /// UPDATE `serialize/deserialize` WHEN THIS STRUCTURE CHANGES
 pub struct ExecutorState<'a, B: AccountStorage> {
     pub backend: &'a B,
     cache: RefCell<Cache>,
     actions: Vec<Action>,
-    stack: Vec<usize>,
+    /// EIP-2929 accessed addresses, in order of the first access.
+    /// Journal for the snapshot rollback, `accessed_addresses_set` is restored from it on deserialize.
+    accessed_addresses: Vec<H160>,
+    accessed_addresses_set: BTreeSet<H160>,
+    /// EIP-2929 accessed storage slots, in order of the first access.
+    /// Journal for the snapshot rollback, `accessed_storage_set` is restored from it on deserialize.
+    accessed_storage: Vec<(H160, U256)>,
+    accessed_storage_set: BTreeSet<(H160, U256)>,
+    /// Accounts created in the current transaction, in order of creation.
+    /// Journal for the snapshot rollback, `created_set` is restored from it on deserialize.
+    created: Vec<H160>,
+    created_set: BTreeSet<H160>,
+    /// EIP-1153 transient storage, discarded at the end of the transaction
+    transient_storage: BTreeMap<(H160, U256), U256>,
+    /// Previous values of the transient storage slots, for the snapshot rollback
+    transient_storage_journal: Vec<((H160, U256), Option<U256>)>,
+    stack: Vec<Snapshot>,
     is_static: u32,
     exit_reason: Option<ExitReason>,
 }
@@ -34,12 +60,23 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
             solana_accounts_partial: BTreeMap::new(),
             block_number: backend.block_number(),
             block_timestamp: backend.block_timestamp(),
+            block_coinbase: backend.block_coinbase(),
+            block_prevrandao: None,
+            simulation: Simulation::default(),
         };
 
         Self {
             backend,
             cache: RefCell::new(cache),
             actions: Vec::new(),
+            accessed_addresses: Vec::new(),
+            accessed_addresses_set: BTreeSet::new(),
+            accessed_storage: Vec::new(),
+            accessed_storage_set: BTreeSet::new(),
+            created: Vec::new(),
+            created_set: BTreeSet::new(),
+            transient_storage: BTreeMap::new(),
+            transient_storage_journal: Vec::new(),
             stack: Vec::new(),
             is_static: 0_u32,
             exit_reason: None,
@@ -49,6 +86,11 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
     pub fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
         self.cache.borrow().serialize(writer)?;
         self.actions.serialize(writer)?;
+        self.accessed_addresses.serialize(writer)?;
+        self.accessed_storage.serialize(writer)?;
+        self.created.serialize(writer)?;
+        self.transient_storage.serialize(writer)?;
+        self.transient_storage_journal.serialize(writer)?;
         self.stack.serialize(writer)?;
         self.is_static.serialize(writer)?;
         self.exit_reason.serialize(writer)?;
@@ -57,10 +99,24 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
     }
 
     pub fn deserialize(buffer: &mut &[u8], backend: &'a B) -> std::io::Result<Self> {
+        let cache = BorshDeserialize::deserialize(buffer)?;
+        let actions = BorshDeserialize::deserialize(buffer)?;
+        let accessed_addresses: Vec<H160> = BorshDeserialize::deserialize(buffer)?;
+        let accessed_storage: Vec<(H160, U256)> = BorshDeserialize::deserialize(buffer)?;
+        let created: Vec<H160> = BorshDeserialize::deserialize(buffer)?;
+
         Ok(Self {
             backend,
-            cache: RefCell::new(BorshDeserialize::deserialize(buffer)?),
-            actions: BorshDeserialize::deserialize(buffer)?,
+            cache: RefCell::new(cache),
+            actions,
+            accessed_addresses_set: accessed_addresses.iter().copied().collect(),
+            accessed_addresses,
+            accessed_storage_set: accessed_storage.iter().copied().collect(),
+            accessed_storage,
+            created_set: created.iter().copied().collect(),
+            created,
+            transient_storage: BorshDeserialize::deserialize(buffer)?,
+            transient_storage_journal: BorshDeserialize::deserialize(buffer)?,
             stack: BorshDeserialize::deserialize(buffer)?,
             is_static: BorshDeserialize::deserialize(buffer)?,
             exit_reason: BorshDeserialize::deserialize(buffer)?,
@@ -77,7 +133,14 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
             self.is_static += 1;
         }
 
-        self.stack.push(self.actions.len());
+        let snapshot = Snapshot {
+            actions_len: self.actions.len(),
+            accessed_addresses_len: self.accessed_addresses.len(),
+            accessed_storage_len: self.accessed_storage.len(),
+            created_len: self.created.len(),
+            transient_storage_len: self.transient_storage_journal.len(),
+        };
+        self.stack.push(snapshot);
     }
 
     /// Commits the state on exit of call or creation.
@@ -89,13 +152,91 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
 
     /// Reverts the state on exit of call or creation.
     pub fn exit_revert(&mut self) {
-        let actions_len = self.stack.pop().unwrap_or(0);
-        self.actions.truncate(actions_len);
+        if let Some(snapshot) = self.stack.pop() {
+            self.actions.truncate(snapshot.actions_len);
+            for address in self.accessed_addresses.drain(snapshot.accessed_addresses_len..) {
+                self.accessed_addresses_set.remove(&address);
+            }
+            for slot in self.accessed_storage.drain(snapshot.accessed_storage_len..) {
+                self.accessed_storage_set.remove(&slot);
+            }
+            for address in self.created.drain(snapshot.created_len..) {
+                self.created_set.remove(&address);
+            }
+
+            let simulation = &mut self.cache.get_mut().simulation;
+            if (simulation.actions_len > self.actions.len()) || (simulation.written_actions_len > self.actions.len()) {
+                *simulation = Simulation::default();
+            }
+            while self.transient_storage_journal.len() > snapshot.transient_storage_len {
+                if let Some((slot, previous)) = self.transient_storage_journal.pop() {
+                    match previous {
+                        Some(value) => self.transient_storage.insert(slot, value),
+                        None => self.transient_storage.remove(&slot),
+                    };
+                }
+            }
+        } else {
+            self.actions.clear();
+            self.accessed_addresses.clear();
+            self.accessed_addresses_set.clear();
+            self.accessed_storage.clear();
+            self.accessed_storage_set.clear();
+            self.created.clear();
+            self.created_set.clear();
+            self.cache.get_mut().simulation = Simulation::default();
+            self.transient_storage.clear();
+            self.transient_storage_journal.clear();
+        }
 
         self.is_static = self.is_static.saturating_sub(1);
     }
 
 
+    /// Marks an address as accessed.
+    /// Returns `true` if the address was already accessed (warm).
+    pub fn access_address(&mut self, address: H160) -> bool {
+        if !self.accessed_addresses_set.insert(address) {
+            return true;
+        }
+
+        self.accessed_addresses.push(address);
+        false
+    }
+
+    /// Marks a storage slot as accessed.
+    /// Returns `true` if the slot was already accessed (warm).
+    pub fn access_storage(&mut self, address: H160, key: U256) -> bool {
+        if !self.accessed_storage_set.insert((address, key)) {
+            return true;
+        }
+
+        self.accessed_storage.push((address, key));
+        false
+    }
+
+    #[must_use]
+    pub fn accessed_addresses(&self) -> &[H160] {
+        &self.accessed_addresses
+    }
+
+    #[must_use]
+    pub fn accessed_storage(&self) -> &[(H160, U256)] {
+        &self.accessed_storage
+    }
+
+    /// Marks an account as created in the current transaction.
+    pub fn set_created(&mut self, address: H160) {
+        if self.created_set.insert(address) {
+            self.created.push(address);
+        }
+    }
+
+    #[must_use]
+    pub fn is_created(&self, address: &H160) -> bool {
+        self.created_set.contains(address)
+    }
+
     /// Increments nonce of an account: increases it by 1.
     pub fn inc_nonce(&mut self, address: H160) {
         let increment = Action::EvmIncrementNonce { address };
@@ -108,6 +249,13 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
         self.actions.push(set_storage);
     }
 
+    /// Adds or changes a record in the transient storage of given account.
+    /// Transient storage is never applied to the accounts.
+    pub fn set_transient_storage(&mut self, address: H160, key: U256, value: U256) {
+        let previous = self.transient_storage.insert((address, key), value);
+        self.transient_storage_journal.push(((address, key), previous));
+    }
+
     /// Adds an Ethereum event log record.
     pub fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) {
         let log = Action::EvmLog { address, topics, data };
@@ -239,6 +387,13 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
         known_storage.unwrap_or_else(|| self.backend.storage(from_address, from_key))
     }
 
+    #[must_use]
+    pub fn transient_storage(&self, address: &H160, key: &U256) -> U256 {
+        self.transient_storage.get(&(*address, *key))
+            .copied()
+            .unwrap_or_default()
+    }
+
     #[must_use]
     pub fn code_size(&self, from_address: &H160) -> U256 {
         let mut code_size = self.backend.code_size(from_address);
@@ -299,13 +454,15 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
         known_valids.map_or_else(|| self.backend.valids(from_address), <[u8]>::to_vec)
     }
 
+    /// Returns hash of one of the 256 most recent blocks (slots) before the transaction block.
     #[must_use]
     pub fn block_hash(&self, number: U256) -> H256 {
-        let origin_block = self.cache.borrow().block_number;
-        let current_block = self.backend.block_number();
-        let offset = current_block.saturating_sub(origin_block);
+        let block_number = self.cache.borrow().block_number;
+
+        if (number >= block_number) || (block_number - number > U256::from(256)) {
+            return H256::default();
+        }
 
-        let number = number.saturating_add(offset);
         self.backend.block_hash(number)
     }
 
@@ -319,46 +476,107 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
         self.cache.borrow().block_timestamp
     }
 
-    pub fn external_account(&self, address: Pubkey) -> Result<OwnedAccountInfo, ProgramError> {
+    #[must_use]
+    pub fn block_coinbase(&self) -> H160 {
+        self.cache.borrow().block_coinbase
+    }
+
+    #[must_use]
+    pub fn block_prevrandao(&self) -> H256 {
         let mut cache = self.cache.borrow_mut();
+        *cache.block_prevrandao.get_or_insert_with(|| self.backend.block_prevrandao())
+    }
 
+    fn is_external_account_written(&self, address: &Pubkey) -> bool {
+        let mut cache = self.cache.borrow_mut();
+        let simulation = &mut cache.simulation;
 
-        let metas = self.actions.iter()
-            .filter_map(|a| if let Action::ExternalInstruction { accounts, .. } = a { Some(accounts) } else { None })
-            .flatten()
-            .collect::<Vec<_>>();
+        for action in &self.actions[simulation.written_actions_len..] {
+            if let Action::ExternalInstruction { accounts, .. } = action {
+                simulation.written.extend(accounts.iter().filter(|m| m.is_writable).map(|m| m.key));
+            }
+        }
+        simulation.written_actions_len = self.actions.len();
 
-        if !metas.iter().any(|m| (m.key == address) && m.is_writable) {
+        simulation.written.contains(address)
+    }
+
+    pub fn external_account(&self, address: Pubkey) -> Result<OwnedAccountInfo, ProgramError> {
+        if !self.is_external_account_written(&address) {
+            let mut cache = self.cache.borrow_mut();
             return Ok(cache.get_account_or_insert(address, self.backend).clone())
         }
 
-        let mut accounts = metas.into_iter()
-            .map(|m| (m.key, cache.get_account_or_insert(m.key, self.backend).clone()))
-            .collect::<BTreeMap<Pubkey, OwnedAccountInfo>>();
+        self.simulate_external_instructions()?;
 
-        for action in &self.actions {
+        let cache = self.cache.borrow();
+        if cache.simulation.unknown.contains(&address) {
+            return Err!(ProgramError::IncorrectProgramId; "Account {} - is changed by the program which can't be emulated", address);
+        }
+
+        Ok(cache.simulation.accounts[&address].clone())
+    }
+
+    /// Simulates queued external instructions against the cached copies of the accounts.
+    /// Returns the return data of the last instruction, so contracts see the results
+    /// of Solana instructions before they are executed at the end of the transaction.
+    pub fn external_instructions_return_data(&self) -> Result<Vec<u8>, ProgramError> {
+        self.simulate_external_instructions()?;
+
+        Ok(self.cache.borrow().simulation.return_data.clone())
+    }
+
+    /// Simulates only the instructions queued since the previous simulation.
+    /// The simulation is restarted from scratch if it fails, or if the queued instructions are reverted.
+    fn simulate_external_instructions(&self) -> Result<(), ProgramError> {
+        let mut cache = self.cache.borrow_mut();
+        let Cache { solana_accounts, simulation, .. } = &mut *cache;
+
+        while simulation.actions_len < self.actions.len() {
+            let action = &self.actions[simulation.actions_len];
             if let Action::ExternalInstruction { program_id, instruction, accounts: meta, .. } = action {
-                match program_id {
-                    program_id if solana_program::system_program::check_id(program_id) => {
-                        crate::external_programs::system::emulate(instruction, meta, &mut accounts)?;
-                    },
-                    program_id if spl_token::check_id(program_id) => {
-                        crate::external_programs::spl_token::emulate(instruction, meta, &mut accounts)?;
-                    },
-                    program_id if spl_associated_token_account::check_id(program_id) => {
-                        crate::external_programs::spl_associated_token::emulate(instruction, meta, &mut accounts)?;
-                    },
-                    program_id if mpl_token_metadata::check_id(program_id) => {
-                        crate::external_programs::metaplex::emulate(instruction, meta, &mut accounts)?;
-                    },
-                    _ => {
-                        return Err!(ProgramError::IncorrectProgramId; "Unknown external program: {}", program_id);
-                    }
+                if let Err(error) = Self::simulate_external_instruction(self.backend, solana_accounts, simulation, program_id, instruction, meta) {
+                    *simulation = Simulation::default();
+                    return Err(error);
                 }
             }
+
+            simulation.actions_len += 1;
         }
 
-        Ok(accounts[&address].clone())
+        Ok(())
+    }
+
+    fn simulate_external_instruction(
+        backend: &B,
+        solana_accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>,
+        simulation: &mut Simulation,
+        program_id: &Pubkey,
+        instruction: &[u8],
+        meta: &[AccountMeta],
+    ) -> Result<(), ProgramError> {
+        if let Some(m) = meta.iter().find(|m| simulation.unknown.contains(&m.key)) {
+            return Err!(ProgramError::IncorrectProgramId; "Account {} - is changed by the program which can't be emulated", m.key);
+        }
+
+        for m in meta {
+            if !simulation.accounts.contains_key(&m.key) {
+                let account = solana_accounts.entry(m.key)
+                    .or_insert_with(|| backend.clone_solana_account(&m.key))
+                    .clone();
+                simulation.accounts.insert(m.key, account);
+            }
+        }
+
+        let return_data = crate::external_programs::emulate(program_id, instruction, meta, &mut simulation.accounts)?;
+        if return_data.is_none() {
+            debug_print!("External program {} can't be emulated", program_id);
+            simulation.unknown.extend(meta.iter().filter(|m| m.is_writable).map(|m| m.key));
+        }
+
+        simulation.return_data = return_data.unwrap_or_default();
+
+        Ok(())
     }
 
     pub fn external_account_partial_cache(&mut self, address: Pubkey, offset: usize, len: usize) -> Result<(), ProgramError> {
@@ -376,6 +594,34 @@ impl<'a, B: AccountStorage> ExecutorState<'a, B> {
         }
     }
 
+    /// Reads a part of the account data, the changes of the queued external instructions are visible.
+    /// Accounts which are not touched in this transaction are read partially from the backend.
+    pub fn external_account_slice(&self, address: Pubkey, offset: usize, len: usize) -> Result<OwnedAccountInfoPartial, ProgramError> {
+        let is_cached = self.cache.borrow().solana_accounts.contains_key(&address);
+        if !is_cached && !self.is_external_account_written(&address) {
+            return self.backend.clone_solana_account_partial(&address, offset, len)
+                .ok_or_else(|| E!(ProgramError::InvalidArgument; "Account {} - invalid data offset", address));
+        }
+
+        let account = self.external_account(address)?;
+        let data = offset.checked_add(len)
+            .and_then(|end| account.data.get(offset..end))
+            .ok_or_else(|| E!(ProgramError::InvalidArgument; "Account {} - invalid data offset", address))?;
+
+        Ok(OwnedAccountInfoPartial {
+            key: account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+            lamports: account.lamports,
+            data: data.to_vec(),
+            data_offset: offset,
+            data_total_len: account.data.len(),
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        })
+    }
+
     pub fn external_account_partial(&self, address: Pubkey) -> Result<OwnedAccountInfoPartial, ProgramError> {
         let cache = self.cache.borrow();
         cache.solana_accounts_partial.get(&address)
@@ -7,7 +7,7 @@ use solana_program::account_info::IntoAccountInfo;
 use solana_program::program_option::COption;
 use solana_program::rent::Rent;
 use solana_program::sysvar::Sysvar;
-use spl_token::state::Mint;
+use spl_token::state::{Account, Mint};
 
 use crate::executor::{OwnedAccountInfo, AccountMeta};
 use solana_program::{
@@ -15,8 +15,14 @@ use solana_program::{
     pubkey::Pubkey, program_error::ProgramError,
     program_pack::Pack
 };
-use mpl_token_metadata::instruction::{MetadataInstruction, CreateMasterEditionArgs, CreateMetadataAccountArgsV3};
-use mpl_token_metadata::state::{Metadata, TokenMetadataAccount, MAX_MASTER_EDITION_LEN, MasterEditionV2, Key, TokenStandard, MAX_METADATA_LEN, CollectionDetails};
+use mpl_token_metadata::instruction::{
+    MetadataInstruction, CreateMasterEditionArgs, CreateMetadataAccountArgsV3,
+    UpdateMetadataAccountArgsV2, MintNewEditionFromMasterEditionViaTokenArgs,
+};
+use mpl_token_metadata::state::{
+    Metadata, TokenMetadataAccount, MAX_MASTER_EDITION_LEN, MasterEditionV2, Key, TokenStandard, MAX_METADATA_LEN,
+    CollectionDetails, Collection, Edition, EditionMarker, MAX_EDITION_LEN, MAX_EDITION_MARKER_SIZE,
+};
 
 
 pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
@@ -28,6 +34,21 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
         MetadataInstruction::CreateMasterEditionV3(args) => {
             create_master_edition_v3(meta, accounts, &args)
         },
+        MetadataInstruction::UpdateMetadataAccountV2(args) => {
+            update_metadata_accounts_v2(meta, accounts, args)
+        },
+        MetadataInstruction::SignMetadata => {
+            sign_metadata(meta, accounts)
+        },
+        MetadataInstruction::SetAndVerifyCollection => {
+            set_and_verify_collection(meta, accounts)
+        },
+        MetadataInstruction::VerifyCollection => {
+            verify_collection(meta, accounts)
+        },
+        MetadataInstruction::MintNewEditionFromMasterEditionViaToken(args) => {
+            mint_new_edition_from_master_edition_via_token(meta, accounts, &args)
+        },
         _ => Err!(ProgramError::InvalidInstructionData; "Unknown Metaplex instruction")
     }
 }
@@ -177,4 +198,306 @@ fn create_master_edition_v3(meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+
+fn update_metadata_accounts_v2(meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>, args: UpdateMetadataAccountArgsV2) -> ProgramResult {
+    let metadata_account_key = &meta[0].key;
+    let update_authority_key = &meta[1].key;
+
+    let mut metadata = read_metadata(accounts, metadata_account_key)?;
+
+    if !meta[1].is_signer || (&metadata.update_authority != update_authority_key) {
+        return Err!(ProgramError::MissingRequiredSignature; "Metaplex: invalid update authority");
+    }
+
+    if let Some(data) = args.data {
+        if !metadata.is_mutable {
+            return Err!(ProgramError::InvalidArgument; "Metaplex: data is immutable");
+        }
+
+        let compatible_data = data.to_v1();
+        assert_data_valid(&compatible_data, update_authority_key, &metadata, false, meta[1].is_signer, true)?;
+        metadata.data = compatible_data;
+
+        assert_collection_update_is_valid(false, &metadata.collection, &data.collection)?;
+        metadata.collection = data.collection;
+
+        assert_valid_use(&data.uses, &metadata.uses)?;
+        metadata.uses = data.uses;
+    }
+
+    if let Some(update_authority) = args.update_authority {
+        metadata.update_authority = update_authority;
+    }
+
+    if let Some(primary_sale_happened) = args.primary_sale_happened {
+        if !primary_sale_happened {
+            return Err!(ProgramError::InvalidArgument; "Metaplex: primary sale can only be flipped to true");
+        }
+        metadata.primary_sale_happened = true;
+    }
+
+    if let Some(is_mutable) = args.is_mutable {
+        if is_mutable && !metadata.is_mutable {
+            return Err!(ProgramError::InvalidArgument; "Metaplex: is mutable can only be flipped to false");
+        }
+        metadata.is_mutable = is_mutable;
+    }
+
+    puff_out_data_fields(&mut metadata);
+
+    write_metadata(accounts, metadata_account_key, &metadata)
+}
+
+
+fn sign_metadata(meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
+    let metadata_account_key = &meta[0].key;
+    let creator_key = &meta[1].key;
+
+    if !meta[1].is_signer {
+        return Err!(ProgramError::MissingRequiredSignature; "Metaplex: creator is not signer");
+    }
+
+    let mut metadata = read_metadata(accounts, metadata_account_key)?;
+
+    let creator = metadata.data.creators.as_mut()
+        .and_then(|creators| creators.iter_mut().find(|c| &c.address == creator_key))
+        .ok_or_else(|| E!(ProgramError::InvalidArgument; "Metaplex: {} is not a creator", creator_key))?;
+    creator.verified = true;
+
+    write_metadata(accounts, metadata_account_key, &metadata)
+}
+
+
+fn set_and_verify_collection(meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
+    let metadata_account_key = &meta[0].key;
+    let collection_authority_key = &meta[1].key;
+    // let _payer_account_key = &meta[2].key;
+    let update_authority_key = &meta[3].key;
+    let collection_mint_key = &meta[4].key;
+    let collection_metadata_key = &meta[5].key;
+    let collection_edition_key = &meta[6].key;
+
+    let mut metadata = read_metadata(accounts, metadata_account_key)?;
+    if &metadata.update_authority != update_authority_key {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid update authority");
+    }
+
+    metadata.collection = Some(Collection { verified: false, key: *collection_mint_key });
+    write_metadata(accounts, metadata_account_key, &metadata)?;
+
+    assert_collection_authority(meta, accounts, collection_authority_key, collection_mint_key, collection_metadata_key, collection_edition_key)?;
+    mark_collection_verified(accounts, metadata_account_key, collection_metadata_key)
+}
+
+
+fn verify_collection(meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
+    let metadata_account_key = &meta[0].key;
+    let collection_authority_key = &meta[1].key;
+    // let _payer_account_key = &meta[2].key;
+    let collection_mint_key = &meta[3].key;
+    let collection_metadata_key = &meta[4].key;
+    let collection_edition_key = &meta[5].key;
+
+    let metadata = read_metadata(accounts, metadata_account_key)?;
+    match metadata.collection {
+        Some(collection) if &collection.key == collection_mint_key => {},
+        _ => return Err!(ProgramError::InvalidArgument; "Metaplex: collection {} is not set", collection_mint_key)
+    }
+
+    assert_collection_authority(meta, accounts, collection_authority_key, collection_mint_key, collection_metadata_key, collection_edition_key)?;
+    mark_collection_verified(accounts, metadata_account_key, collection_metadata_key)
+}
+
+
+fn mint_new_edition_from_master_edition_via_token(
+    meta: &[AccountMeta],
+    accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>,
+    args: &MintNewEditionFromMasterEditionViaTokenArgs
+) -> ProgramResult {
+    let new_metadata_key      = &meta[0].key;
+    let new_edition_key       = &meta[1].key;
+    let master_edition_key    = &meta[2].key;
+    let new_mint_key          = &meta[3].key;
+    let edition_marker_key    = &meta[4].key;
+    let new_mint_authority_key = &meta[5].key;
+    // let _payer_account_key  = &meta[6].key;
+    let token_account_owner_key = &meta[7].key;
+    let token_account_key     = &meta[8].key;
+    let new_update_authority_key = &meta[9].key;
+    let master_metadata_key   = &meta[10].key;
+
+    let rent = Rent::get()?;
+    let edition = args.edition;
+
+    let master_metadata = read_metadata(accounts, master_metadata_key)?;
+
+    let mut master_edition = MasterEditionV2::safe_deserialize(&accounts[master_edition_key].data)?;
+    if master_edition.max_supply.map_or(false, |max_supply| edition > max_supply) || (edition == 0) {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid edition number {}", edition);
+    }
+
+    let token_account = Account::unpack(&accounts[token_account_key].data)?;
+    if (&token_account.owner != token_account_owner_key) || !meta[7].is_signer {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid token account owner");
+    }
+    if (token_account.mint != master_metadata.mint) || (token_account.amount < 1) {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: token account doesn't hold master edition token");
+    }
+
+    let mut new_mint = Mint::unpack(&accounts[new_mint_key].data)?;
+    if (new_mint.decimals != 0) || (new_mint.supply != 1) {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: edition mint must have exactly one token");
+    }
+    if (new_mint.mint_authority != COption::Some(*new_mint_authority_key)) || !meta[5].is_signer {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid mint authority");
+    }
+
+    {
+        let marker_account = accounts.get_mut(edition_marker_key).unwrap();
+
+        let mut marker = if marker_account.data.is_empty() {
+            marker_account.data.resize(MAX_EDITION_MARKER_SIZE, 0);
+            marker_account.owner = mpl_token_metadata::ID;
+            marker_account.lamports = marker_account.lamports.max(rent.minimum_balance(MAX_EDITION_MARKER_SIZE));
+
+            EditionMarker { key: Key::EditionMarker, ledger: [0; 31] }
+        } else {
+            EditionMarker::safe_deserialize(&marker_account.data)?
+        };
+
+        if marker.edition_taken(edition)? {
+            return Err!(ProgramError::InvalidArgument; "Metaplex: edition {} is already taken", edition);
+        }
+        marker.insert_edition(edition)?;
+        marker.serialize(&mut marker_account.data.as_mut_slice())?;
+    }
+
+    {
+        let mut metadata: Metadata = {
+            let metadata_account = accounts.get_mut(new_metadata_key).unwrap();
+            metadata_account.data.resize(MAX_METADATA_LEN, 0);
+            metadata_account.owner = mpl_token_metadata::ID;
+            metadata_account.lamports = metadata_account.lamports.max(rent.minimum_balance(MAX_METADATA_LEN));
+
+            let metadata_account_info = metadata_account.into_account_info();
+            Metadata::from_account_info(&metadata_account_info)?
+        };
+
+        metadata.key = Key::MetadataV1;
+        metadata.mint = *new_mint_key;
+        metadata.update_authority = *new_update_authority_key;
+        metadata.data = master_metadata.data.clone();
+        metadata.primary_sale_happened = master_metadata.primary_sale_happened;
+        metadata.is_mutable = master_metadata.is_mutable;
+        metadata.token_standard = Some(TokenStandard::NonFungibleEdition);
+        metadata.collection = master_metadata.collection.clone();
+        metadata.uses = master_metadata.uses.clone();
+        metadata.collection_details = None;
+
+        let edition_seeds = &[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::ID.as_ref(),
+            new_mint_key.as_ref(),
+            mpl_token_metadata::state::EDITION.as_bytes(),
+        ];
+        let (_, edition_bump_seed) = Pubkey::find_program_address(edition_seeds, &mpl_token_metadata::ID);
+        metadata.edition_nonce = Some(edition_bump_seed);
+
+        write_metadata(accounts, new_metadata_key, &metadata)?;
+    }
+
+    {
+        let edition_account = accounts.get_mut(new_edition_key).unwrap();
+        edition_account.data.resize(MAX_EDITION_LEN, 0);
+        edition_account.owner = mpl_token_metadata::ID;
+        edition_account.lamports = edition_account.lamports.max(rent.minimum_balance(MAX_EDITION_LEN));
+
+        let new_edition = Edition { key: Key::EditionV1, parent: *master_edition_key, edition };
+        new_edition.serialize(&mut edition_account.data.as_mut_slice())?;
+    }
+
+    {
+        master_edition.supply = master_edition.supply.max(edition);
+
+        let master_edition_account = accounts.get_mut(master_edition_key).unwrap();
+        master_edition.serialize(&mut master_edition_account.data.as_mut_slice())?;
+    }
+
+    {
+        new_mint.mint_authority = COption::Some(*new_edition_key);
+        if new_mint.freeze_authority.is_some() {
+            new_mint.freeze_authority = COption::Some(*new_edition_key);
+        }
+
+        let mint_account = accounts.get_mut(new_mint_key).unwrap();
+        new_mint.pack_into_slice(&mut mint_account.data);
+    }
+
+    Ok(())
+}
+
+
+fn assert_collection_authority(
+    meta: &[AccountMeta],
+    accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>,
+    collection_authority_key: &Pubkey,
+    collection_mint_key: &Pubkey,
+    collection_metadata_key: &Pubkey,
+    collection_edition_key: &Pubkey,
+) -> ProgramResult {
+    if !meta[1].is_signer {
+        return Err!(ProgramError::MissingRequiredSignature; "Metaplex: collection authority is not signer");
+    }
+
+    let collection_metadata = read_metadata(accounts, collection_metadata_key)?;
+    if &collection_metadata.mint != collection_mint_key {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid collection mint");
+    }
+    if &collection_metadata.update_authority != collection_authority_key {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: invalid collection authority");
+    }
+
+    let collection_edition = &accounts[collection_edition_key];
+    if !mpl_token_metadata::check_id(&collection_edition.owner) || collection_edition.data.is_empty() {
+        return Err!(ProgramError::InvalidArgument; "Metaplex: collection must have master edition");
+    }
+
+    Ok(())
+}
+
+
+fn mark_collection_verified(
+    accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>,
+    metadata_account_key: &Pubkey,
+    collection_metadata_key: &Pubkey,
+) -> ProgramResult {
+    let mut metadata = read_metadata(accounts, metadata_account_key)?;
+    if let Some(collection) = metadata.collection.as_mut() {
+        collection.verified = true;
+    }
+    write_metadata(accounts, metadata_account_key, &metadata)?;
+
+    let mut collection_metadata = read_metadata(accounts, collection_metadata_key)?;
+    if let Some(CollectionDetails::V1 { size }) = collection_metadata.collection_details.as_mut() {
+        *size = size.saturating_add(1);
+        write_metadata(accounts, collection_metadata_key, &collection_metadata)?;
+    }
+
+    Ok(())
+}
+
+
+fn read_metadata(accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>, key: &Pubkey) -> Result<Metadata, ProgramError> {
+    let metadata_info = accounts.get_mut(key).unwrap().into_account_info();
+    Metadata::from_account_info(&metadata_info)
+}
+
+
+fn write_metadata(accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>, key: &Pubkey, metadata: &Metadata) -> ProgramResult {
+    let metadata_account = accounts.get_mut(key).unwrap();
+    metadata.serialize(&mut metadata_account.data.as_mut_slice())?;
+
+    Ok(())
+}
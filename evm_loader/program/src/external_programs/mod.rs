@@ -1,4 +1,45 @@
+use std::collections::BTreeMap;
+
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::executor::{AccountMeta, OwnedAccountInfo};
+
 pub mod spl_token;
 pub mod spl_associated_token;
 pub mod system;
-pub mod metaplex;
\ No newline at end of file
+pub mod metaplex;
+
+
+/// Emulates the instruction of a known Solana program against the copies of the accounts.
+/// Returns the return data of the instruction, or `None` if the program can't be emulated.
+pub fn emulate(
+    program_id: &Pubkey,
+    instruction: &[u8],
+    meta: &[AccountMeta],
+    accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>
+) -> Result<Option<Vec<u8>>, ProgramError> {
+    match program_id {
+        program_id if solana_program::system_program::check_id(program_id) => {
+            system::emulate(instruction, meta, accounts)?;
+        },
+        program_id if ::spl_token::check_id(program_id) => {
+            if let Some(return_data) = spl_token::emulate_return_data(instruction, meta, accounts)? {
+                return Ok(Some(return_data));
+            }
+
+            spl_token::emulate(instruction, meta, accounts)?;
+        },
+        program_id if ::spl_token_2022::check_id(program_id) => {
+            spl_token::emulate_2022(instruction, meta, accounts)?;
+        },
+        program_id if ::spl_associated_token_account::check_id(program_id) => {
+            spl_associated_token::emulate(instruction, meta, accounts)?;
+        },
+        program_id if ::mpl_token_metadata::check_id(program_id) => {
+            metaplex::emulate(instruction, meta, accounts)?;
+        },
+        _ => return Ok(None)
+    }
+
+    Ok(Some(vec![]))
+}
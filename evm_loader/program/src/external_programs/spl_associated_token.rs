@@ -8,6 +8,7 @@ use solana_program::{
     program_error::ProgramError, rent::Rent, sysvar::Sysvar, program_pack::Pack
 };
 use spl_associated_token_account::instruction::AssociatedTokenAccountInstruction;
+use spl_token_2022::extension::{ExtensionType, StateWithExtensions};
 
 
 pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
@@ -18,7 +19,7 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
             .map_err(|_| ProgramError::InvalidInstructionData)?
     };
     
-    if instruction != AssociatedTokenAccountInstruction::Create {
+    if (instruction != AssociatedTokenAccountInstruction::Create) && (instruction != AssociatedTokenAccountInstruction::CreateIdempotent) {
         return Err!(ProgramError::InvalidInstructionData; "Unknown spl_associated_token instruction");
     }
 
@@ -29,11 +30,29 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
     // let system_program_key = &meta[4].key;
     let spl_token_program_key = &meta[5].key;
 
+    if (instruction == AssociatedTokenAccountInstruction::CreateIdempotent)
+        && (accounts[associated_token_account_key].owner == *spl_token_program_key)
+    {
+        return Ok(());
+    }
+
+    let is_token_2022 = spl_token_2022::check_id(spl_token_program_key);
+    let space = if is_token_2022 {
+        let mint = &accounts[spl_token_mint_key];
+        let mint_extensions = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint.data)?.get_extension_types()?;
+
+        let mut account_extensions = ExtensionType::get_required_init_account_extensions(&mint_extensions);
+        account_extensions.push(ExtensionType::ImmutableOwner);
+        ExtensionType::get_account_len::<spl_token_2022::state::Account>(&account_extensions)
+    } else {
+        spl_token::state::Account::LEN
+    };
+
     let required_lamports = {
         let associated_token_account = &accounts[associated_token_account_key];
 
         let rent = Rent::get()?;
-        rent.minimum_balance(spl_token::state::Account::LEN)
+        rent.minimum_balance(space)
             .max(1)
             .saturating_sub(associated_token_account.lamports)
     };
@@ -54,12 +73,22 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
         }
         
         associated_token_account.lamports += required_lamports;
-        associated_token_account.owner = spl_token::ID;
-        associated_token_account.data.resize(spl_token::state::Account::LEN, 0);
+        associated_token_account.owner = *spl_token_program_key;
+        associated_token_account.data.resize(space, 0);
+    }
+
+    if is_token_2022 {
+        let initialize_immutable_owner = spl_token_2022::instruction::initialize_immutable_owner(
+            spl_token_program_key,
+            associated_token_account_key,
+        )?;
+
+        let meta: Vec<AccountMeta> = initialize_immutable_owner.accounts.into_iter().map(AccountMeta::from_solana_meta).collect();
+        super::spl_token::emulate_2022(&initialize_immutable_owner.data, &meta, accounts)?;
     }
 
 
-    let initialize_account = spl_token::instruction::initialize_account3(
+    let initialize_account = spl_token_2022::instruction::initialize_account3(
         spl_token_program_key,
         associated_token_account_key,
         spl_token_mint_key,
@@ -68,5 +97,9 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
 
     let instruction: &[u8] = &initialize_account.data;
     let meta: Vec<AccountMeta> = initialize_account.accounts.into_iter().map(AccountMeta::from_solana_meta).collect();
-    super::spl_token::emulate(instruction, &meta, accounts)
+    if is_token_2022 {
+        super::spl_token::emulate_2022(instruction, &meta, accounts)
+    } else {
+        super::spl_token::emulate(instruction, &meta, accounts)
+    }
 }
\ No newline at end of file
@@ -4,11 +4,48 @@ use crate::executor::{OwnedAccountInfo, AccountMeta};
 use solana_program::{
     entrypoint::ProgramResult,
     pubkey::Pubkey,
-    account_info::{AccountInfo, IntoAccountInfo}, program_error::ProgramError
+    account_info::{AccountInfo, IntoAccountInfo}, program_error::ProgramError,
+    program_pack::Pack
 };
 use spl_token::instruction::TokenInstruction;
+use spl_token_2022::instruction::TokenInstruction as TokenInstruction2022;
 
 
+/// Emulates the instructions which don't change the accounts and only return data
+pub fn emulate_return_data(
+    instruction: &[u8],
+    meta: &[AccountMeta],
+    accounts: &BTreeMap<Pubkey, OwnedAccountInfo>
+) -> Result<Option<Vec<u8>>, ProgramError> {
+    let mint_decimals = || -> Result<u8, ProgramError> {
+        let mint = meta.first()
+            .and_then(|m| accounts.get(&m.key))
+            .ok_or_else(|| E!(ProgramError::NotEnoughAccountKeys; "SPL Token: mint account expected"))?;
+
+        if !spl_token::check_id(&mint.owner) {
+            return Err!(ProgramError::IncorrectProgramId; "SPL Token: account {} is not a mint", mint.key);
+        }
+
+        spl_token::state::Mint::unpack(&mint.data).map(|mint| mint.decimals)
+    };
+
+    match TokenInstruction::unpack(instruction)? {
+        TokenInstruction::GetAccountDataSize => {
+            let size = spl_token::state::Account::LEN as u64;
+            Ok(Some(size.to_le_bytes().to_vec()))
+        }
+        TokenInstruction::AmountToUiAmount { amount } => {
+            let ui_amount = spl_token::amount_to_ui_amount_string_trimmed(amount, mint_decimals()?);
+            Ok(Some(ui_amount.into_bytes()))
+        }
+        TokenInstruction::UiAmountToAmount { ui_amount } => {
+            let amount = spl_token::try_ui_amount_into_amount(ui_amount.to_string(), mint_decimals()?)?;
+            Ok(Some(amount.to_le_bytes().to_vec()))
+        }
+        _ => Ok(None)
+    }
+}
+
 pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
     let accounts_info = accounts.iter_mut()
         .map(|(key, a)| (*key, a.into_account_info()))
@@ -92,4 +129,50 @@ pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap
             Err!(ProgramError::InvalidInstructionData; "SPL Token: unknown instrtuction")
         }
     }
-}
\ No newline at end of file
+}
+
+/// Token-2022 instructions are processed by the program itself,
+/// only the subset also supported for the legacy SPL Token is allowed
+pub fn emulate_2022(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
+    let accounts_info = accounts.iter_mut()
+        .map(|(key, a)| (*key, a.into_account_info()))
+        .collect::<BTreeMap<Pubkey, AccountInfo>>();
+
+    let instruction_accounts: Vec<AccountInfo> = meta.iter().map(|a| {
+        let mut info = accounts_info[&a.key].clone();
+        info.is_writable = a.is_writable;
+        info.is_signer = a.is_signer;
+        info
+    }).collect();
+
+    let allowed = matches!(TokenInstruction2022::unpack(instruction)?,
+        TokenInstruction2022::InitializeMint { .. }
+        | TokenInstruction2022::InitializeMint2 { .. }
+        | TokenInstruction2022::InitializeAccount
+        | TokenInstruction2022::InitializeAccount2 { .. }
+        | TokenInstruction2022::InitializeAccount3 { .. }
+        | TokenInstruction2022::InitializeMultisig { .. }
+        | TokenInstruction2022::InitializeMultisig2 { .. }
+        | TokenInstruction2022::InitializeImmutableOwner
+        | TokenInstruction2022::Transfer { .. }
+        | TokenInstruction2022::Approve { .. }
+        | TokenInstruction2022::Revoke
+        | TokenInstruction2022::SetAuthority { .. }
+        | TokenInstruction2022::MintTo { .. }
+        | TokenInstruction2022::Burn { .. }
+        | TokenInstruction2022::CloseAccount
+        | TokenInstruction2022::FreezeAccount
+        | TokenInstruction2022::ThawAccount
+        | TokenInstruction2022::TransferChecked { .. }
+        | TokenInstruction2022::ApproveChecked { .. }
+        | TokenInstruction2022::MintToChecked { .. }
+        | TokenInstruction2022::BurnChecked { .. }
+        | TokenInstruction2022::SyncNative
+    );
+
+    if !allowed {
+        return Err!(ProgramError::InvalidInstructionData; "SPL Token-2022: unsupported instruction");
+    }
+
+    spl_token_2022::processor::Processor::process(&spl_token_2022::ID, &instruction_accounts, instruction)
+}
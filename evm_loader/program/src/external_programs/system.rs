@@ -12,7 +12,8 @@ use solana_program::{
 
 
 pub fn emulate(instruction: &[u8], meta: &[AccountMeta], accounts: &mut BTreeMap<Pubkey, OwnedAccountInfo>) -> ProgramResult {
-    let system_instruction: SystemInstruction = bincode::deserialize(instruction).unwrap();
+    let system_instruction: SystemInstruction = bincode::deserialize(instruction)
+        .map_err(|_| E!(ProgramError::InvalidInstructionData; "System: invalid instruction data"))?;
     match system_instruction {
         SystemInstruction::CreateAccount { lamports, space, owner } => {
             let funder_key = &meta[0].key;
@@ -21,6 +21,22 @@ pub enum EvmInstruction {
     ///   5. `[]` System program.
     DepositV03,
 
+    /// Deposits NEON tokens to an Ether account (V4).
+    /// Same as `DepositV03`, but transfers with `TransferChecked`,
+    /// so the NEON token mint may be owned by SPL Token-2022.
+    /// Ether account is credited with the amount received by the pool.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` NEON token source account.
+    ///   1. `[writable]` NEON token pool (destination) account.
+    ///   2. `[writable]` Ether account to store balance of NEONs.
+    ///   3. `[]` SPL Token or SPL Token-2022 program id.
+    ///   4. `[writeable,signer]` Funding account (must be a system account).
+    ///   5. `[]` System program.
+    ///   6. `[]` NEON token mint.
+    DepositV04,
+
     /// Create Ethereum account V3
     /// # Account references
     ///   0. [WRITE, SIGNER] Funding account
@@ -88,6 +104,7 @@ impl EvmInstruction {
             0x27 => Self::DepositV03,                               // 39
             0x28 => Self::CreateAccountV03,                         // 40
             0x29 => Self::CreateMainTreasury,                       // 41
+            0x2a => Self::DepositV04,                               // 42
 
             _ => return Err(ProgramError::InvalidInstructionData),
         })
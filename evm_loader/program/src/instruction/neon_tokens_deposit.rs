@@ -7,7 +7,7 @@ use solana_program::{
     pubkey::Pubkey,
 };
 use solana_program::program::invoke_signed;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 use crate::account::{ACCOUNT_SEED_VERSION, EthereumAccount, Operator, program, token};
 
@@ -18,12 +18,14 @@ struct Accounts<'a> {
     token_program: program::Token<'a>,
     operator: Operator<'a>,
     system_program: program::System<'a>,
+    /// NEON token mint, passed to `DepositV04` only
+    mint: Option<token::Mint<'a>>,
 }
 
 const AUTHORITY_SEED: &[u8] = b"Deposit";
 
 impl<'a> Accounts<'a> {
-    pub fn from_slice(accounts: &'a [AccountInfo<'a>]) -> Result<Accounts<'a>, ProgramError> {
+    pub fn from_slice(accounts: &'a [AccountInfo<'a>], with_mint: bool) -> Result<Accounts<'a>, ProgramError> {
         Ok(Accounts {
             source: token::State::from_account(&accounts[0])?,
             pool: token::State::from_account(&accounts[1])?,
@@ -31,14 +33,27 @@ impl<'a> Accounts<'a> {
             token_program: program::Token::from_account(&accounts[3])?,
             operator: unsafe { Operator::from_account_not_whitelisted(&accounts[4]) }?,
             system_program: program::System::from_account(&accounts[5])?,
+            mint: if with_mint { Some(token::Mint::from_account(&accounts[6])?) } else { None },
         })
     }
 }
 
+/// `DepositV03`: transfers the delegated amount with SPL Token `Transfer`
 pub fn process<'a>(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], instruction: &[u8]) -> ProgramResult {
     solana_program::msg!("Instruction: Deposit");
 
-    let parsed_accounts = Accounts::from_slice(accounts)?;
+    let parsed_accounts = Accounts::from_slice(accounts, false)?;
+    let ethereum_address = H160::from(array_ref![instruction, 0, 20]);
+
+    let ethereum_bump_seed = validate(program_id, &parsed_accounts, &ethereum_address)?;
+    execute(program_id, &parsed_accounts, ethereum_address, ethereum_bump_seed)
+}
+
+/// `DepositV04`: transfers the delegated amount with `TransferChecked`, supports Token-2022 NEON mint
+pub fn process_v04<'a>(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], instruction: &[u8]) -> ProgramResult {
+    solana_program::msg!("Instruction: Deposit V4");
+
+    let parsed_accounts = Accounts::from_slice(accounts, true)?;
     let ethereum_address = H160::from(array_ref![instruction, 0, 20]);
 
     let ethereum_bump_seed = validate(program_id, &parsed_accounts, &ethereum_address)?;
@@ -62,6 +77,36 @@ fn validate(
         );
     }
 
+    if let Some(mint) = &accounts.mint {
+        if mint.info.key != &crate::config::token_mint::id() {
+            return Err!(
+                ProgramError::InvalidArgument;
+                "Account {} - expected Neon Token mint",
+                mint.info.key
+            );
+        }
+
+        if mint.info.owner != accounts.token_program.key {
+            return Err!(
+                ProgramError::InvalidArgument;
+                "Account {} - expected owner {}",
+                mint.info.key,
+                accounts.token_program.key
+            );
+        }
+    }
+
+    for token_account in [&accounts.source.info, &accounts.pool.info] {
+        if token_account.owner != accounts.token_program.key {
+            return Err!(
+                ProgramError::InvalidArgument;
+                "Account {} - expected owner {}",
+                token_account.key,
+                accounts.token_program.key
+            );
+        }
+    }
+
     if accounts.source.mint != crate::config::token_mint::id() {
         return Err!(
             ProgramError::InvalidArgument;
@@ -71,9 +116,10 @@ fn validate(
     }
 
     let (authority_address, _) = Pubkey::find_program_address(&[AUTHORITY_SEED], program_id);
-    let expected_pool_address = get_associated_token_address(
+    let expected_pool_address = get_associated_token_address_with_program_id(
         &authority_address,
         &crate::config::token_mint::id(),
+        accounts.token_program.key,
     );
 
     if accounts.pool.info.key != &expected_pool_address {
@@ -124,23 +170,44 @@ fn execute<'a>(
         &[ethereum_bump_seed],
     ]];
 
-    let instruction = spl_token::instruction::transfer(
-        accounts.token_program.key,
-        accounts.source.info.key,
-        accounts.pool.info.key,
-        accounts.ethereum_account.key,
-        &[],
-        accounts.source.delegated_amount,
-    )?;
-
-    let account_infos: &[AccountInfo] = &[
+    let mut account_infos = vec![
         accounts.source.info.clone(),
         accounts.pool.info.clone(),
         accounts.ethereum_account.clone(),
         accounts.token_program.clone(),
     ];
 
-    invoke_signed(&instruction, account_infos, signers_seeds)?;
+    let instruction = match &accounts.mint {
+        Some(mint) => {
+            account_infos.push(mint.info.clone());
+
+            spl_token_2022::instruction::transfer_checked(
+                accounts.token_program.key,
+                accounts.source.info.key,
+                mint.info.key,
+                accounts.pool.info.key,
+                accounts.ethereum_account.key,
+                &[],
+                accounts.source.delegated_amount,
+                mint.decimals,
+            )?
+        }
+        None => spl_token::instruction::transfer(
+            accounts.token_program.key,
+            accounts.source.info.key,
+            accounts.pool.info.key,
+            accounts.ethereum_account.key,
+            &[],
+            accounts.source.delegated_amount,
+        )?,
+    };
+
+    invoke_signed(&instruction, &account_infos, signers_seeds)?;
+
+    // Token-2022 mints may withhold a transfer fee, credit only what the pool received
+    let pool_after = token::State::from_account(accounts.pool.info)?;
+    let received = pool_after.amount.checked_sub(accounts.pool.amount)
+        .ok_or_else(|| E!(ProgramError::InvalidAccountData; "Account {} - pool balance decreased", accounts.pool.info.key))?;
 
     if solana_program::system_program::check_id(accounts.ethereum_account.owner) {
         EthereumAccount::create_and_init_account(
@@ -156,7 +223,7 @@ fn execute<'a>(
 
     assert!(crate::config::token_mint::decimals() <= 18);
     let additional_decimals: u32 = (18 - crate::config::token_mint::decimals()).into();
-    let deposit = U256::from(accounts.source.delegated_amount) * U256::from(10_u64.pow(additional_decimals));
+    let deposit = U256::from(received) * U256::from(10_u64.pow(additional_decimals));
     let mut ethereum_account = EthereumAccount::from_account(program_id, accounts.ethereum_account)?;
     ethereum_account.balance = ethereum_account.balance.checked_add(deposit)
         .ok_or_else(||
@@ -167,5 +234,7 @@ fn execute<'a>(
             )
         )?;
 
+    program::Neon::on_neon_transfer(H160::zero(), ethereum_address, deposit);
+
     Ok(())
 }
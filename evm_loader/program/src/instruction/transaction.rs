@@ -8,7 +8,7 @@ use crate::account_storage::{AccountsReadiness, ProgramAccountStorage};
 use crate::config::{EVM_STEPS_MIN, EVM_STEPS_LAST_ITERATION_MAX, PAYMENT_TO_TREASURE};
 use crate::executor::{Action, Gasometer, Machine};
 use crate::state_account::Deposit;
-use crate::transaction::{check_ethereum_transaction, Transaction};
+use crate::transaction::{check_ethereum_transaction, split_gas_cost, Transaction};
 
 pub struct Accounts<'a> {
     pub operator: Operator<'a>,
@@ -35,13 +35,15 @@ pub fn do_begin<'a>(
     account_storage.check_for_blocked_accounts()?;
     account_storage.block_accounts(true);
 
+    let gas_price = trx.effective_gas_price();
     let mut executor = Machine::new(caller, account_storage)?;
 
     if let Some(code_address) = trx.to {
-        executor.call_begin(caller, code_address, trx.call_data, trx.value, trx.gas_limit, trx.gas_price)
+        executor.call_begin(caller, code_address, trx.call_data, trx.value, trx.gas_limit, gas_price)
     } else {
-        executor.create_begin(caller, trx.call_data, trx.value, trx.gas_limit, trx.gas_price)
+        executor.create_begin(caller, trx.call_data, trx.value, trx.gas_limit, gas_price)
     }?;
+    executor.apply_access_list(&trx.access_list)?;
 
     executor.save_into(&mut storage);
 
@@ -104,6 +106,8 @@ fn execute_steps(
     result
 }
 
+/// Charges the caller with `used_gas * effective_gas_price`.
+/// The base fee part of EIP-1559 transactions is burned, the rest goes to the operator.
 fn pay_gas_cost<'a>(
     used_gas: U256,
     operator_ether_account: EthereumAccount<'a>,
@@ -112,14 +116,14 @@ fn pay_gas_cost<'a>(
 ) -> ProgramResult {
     debug_print!("pay_gas_cost {}", used_gas);
 
-    // Can overflow in malicious transaction
-    let value = used_gas.saturating_mul(storage.gas_price);
+    let (base_fee, value) = split_gas_cost(used_gas, storage.gas_price, storage.base_fee_per_gas);
     storage.gas_used = storage.gas_used.saturating_add(used_gas);
 
     account_storage.transfer_gas_payment(
         storage.caller,
         operator_ether_account,
         value,
+        base_fee,
     )?;
 
     Ok(())
@@ -1,5 +1,5 @@
 use crate::account::{Operator, program, EthereumAccount, Treasury};
-use crate::transaction::{check_ethereum_transaction, Transaction, recover_caller_address};
+use crate::transaction::{check_ethereum_transaction, Transaction, recover_caller_address, split_gas_cost};
 use crate::account_storage::{AccountsReadiness, ProgramAccountStorage};
 use arrayref::{array_ref};
 use evm::{H160};
@@ -47,6 +47,7 @@ pub fn process<'a>(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], inst
     let mut account_storage = ProgramAccountStorage::new(
         program_id,
         &accounts.operator,
+        accounts.operator_ether_account.address,
         Some(&accounts.system_program),
         accounts.remaining_accounts,
     )?;
@@ -84,6 +85,9 @@ fn execute<'a>(
 
     accounts.system_program.transfer(&accounts.operator, &accounts.treasury, crate::config::PAYMENT_TO_TREASURE)?;
 
+    let gas_price = trx.effective_gas_price();
+    let base_fee_per_gas = trx.base_fee_per_gas();
+
     let (exit_reason, apply_state) = {
         let mut executor = Machine::new(caller_address, account_storage)?;
 
@@ -97,8 +101,9 @@ fn execute<'a>(
             trx.call_data,
             trx.value,
             trx.gas_limit,
-            trx.gas_price,
+            gas_price,
         )?;
+        executor.apply_access_list(&trx.access_list)?;
 
         let exit_reason = executor.execute();
         let actions = executor.into_state_actions();
@@ -131,8 +136,8 @@ fn execute<'a>(
 
     solana_program::log::sol_log_data(&[b"IX_GAS", &used_gas.as_u64().to_le_bytes()]);
 
-    let gas_cost = used_gas.saturating_mul(trx.gas_price);
-    account_storage.transfer_gas_payment(caller_address, accounts.operator_ether_account, gas_cost)?;
+    let (base_fee, gas_cost) = split_gas_cost(used_gas, gas_price, base_fee_per_gas);
+    account_storage.transfer_gas_payment(caller_address, accounts.operator_ether_account, gas_cost, base_fee)?;
 
     accounts.neon_program.on_return(exit_reason, used_gas);
     
@@ -34,6 +34,7 @@ pub fn process<'a>(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], inst
     let mut account_storage = ProgramAccountStorage::new(
         program_id,
         &accounts.operator,
+        accounts.operator_ether_account.address,
         Some(&accounts.system_program),
         accounts.remaining_accounts,
     )?;
@@ -103,4 +104,4 @@ pub fn execute<'a>(
             Err!(ProgramError::InvalidAccountData; "Account {} - expected Holder or State", holder_or_storage_info.key)
         }
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
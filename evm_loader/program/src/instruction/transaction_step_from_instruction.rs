@@ -33,6 +33,7 @@ pub fn process<'a>(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], inst
     let mut account_storage = ProgramAccountStorage::new(
         program_id,
         &accounts.operator,
+        accounts.operator_ether_account.address,
         Some(&accounts.system_program),
         accounts.remaining_accounts,
     )?;
@@ -0,0 +1,433 @@
+//! Solidity ABI decoding and encoding of the precompile inputs and outputs.
+//!
+//! All reads are bounds checked, so malformed input is reported as an error
+//! and the precompile reverts with `Error(string)` payload instead of panicking.
+
+use std::convert::{Infallible, TryInto};
+use std::fmt;
+
+use evm::{Capture, ExitReason, ExitRevert, ExitSucceed, H160, U256};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// keccak256("Error(string)")[..4]
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+const WORD_SIZE: usize = 32;
+
+
+/// Failure of the precompile call, the message is returned in the `Error(string)` revert payload.
+/// Created by the `Err!` and `E!` macros of the precompiles or converted from `ProgramError`.
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+}
+
+impl From<ProgramError> for Error {
+    fn from(error: ProgramError) -> Self {
+        Self { message: error.to_string() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Splits the call data into the method selector and the arguments
+pub fn split_selector(input: &[u8]) -> Result<([u8; 4], Decoder<'_>)> {
+    if input.len() < 4 {
+        return Err!(ProgramError::InvalidInstructionData; "ABI: method selector is missing");
+    }
+
+    let (selector, arguments) = input.split_at(4);
+    let selector = [selector[0], selector[1], selector[2], selector[3]];
+
+    Ok((selector, Decoder::new(arguments)))
+}
+
+/// Result of the precompile call: output on success, `Error(string)` revert on failure
+#[must_use]
+pub fn exit(result: Result<Vec<u8>>) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+    result.map_or_else(
+        |error| revert(&error.message),
+        |output| Capture::Exit((ExitSucceed::Returned.into(), output))
+    )
+}
+
+/// Reverts with `Error(string)` payload
+#[must_use]
+pub fn revert(message: &str) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+    let mut payload = ERROR_SELECTOR.to_vec();
+    payload.extend(encode(&[Token::String(message.to_string())]));
+
+    Capture::Exit((ExitRevert::Reverted.into(), payload))
+}
+
+
+/// Arguments of the ABI encoded call, values are addressed by the index of their head word.
+/// Offsets of the dynamic values are relative to the beginning of the decoder data.
+#[derive(Clone, Copy)]
+pub struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    #[must_use]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn word(&self, index: usize) -> Result<&'a [u8; 32]> {
+        let begin = index.checked_mul(WORD_SIZE)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: argument index {} is too big", index))?;
+
+        self.slice(begin, WORD_SIZE)?
+            .try_into()
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: input is too short"))
+    }
+
+    pub fn u256(&self, index: usize) -> Result<U256> {
+        self.word(index).map(|word| U256::from_big_endian_fast(word))
+    }
+
+    pub fn u64(&self, index: usize) -> Result<u64> {
+        let value = self.u256(index)?;
+        value.try_into()
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: value {} is too big", value))
+    }
+
+    pub fn u16(&self, index: usize) -> Result<u16> {
+        let value = self.u64(index)?;
+        value.try_into()
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: value {} is too big", value))
+    }
+
+    pub fn u8(&self, index: usize) -> Result<u8> {
+        let value = self.u64(index)?;
+        value.try_into()
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: value {} is too big", value))
+    }
+
+    pub fn usize(&self, index: usize) -> Result<usize> {
+        let value = self.u64(index)?;
+        value.try_into()
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: value {} is too big", value))
+    }
+
+    pub fn bool(&self, index: usize) -> Result<bool> {
+        match self.u64(index)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err!(ProgramError::InvalidInstructionData; "ABI: invalid bool value {}", value),
+        }
+    }
+
+    pub fn address(&self, index: usize) -> Result<H160> {
+        let word = self.word(index)?;
+        if word[..12].iter().any(|byte| *byte != 0) {
+            return Err!(ProgramError::InvalidInstructionData; "ABI: invalid address {}", hex::encode(word));
+        }
+
+        Ok(H160::from_slice(&word[12..]))
+    }
+
+    pub fn bytes32(&self, index: usize) -> Result<[u8; 32]> {
+        self.word(index).map(|word| *word)
+    }
+
+    pub fn pubkey(&self, index: usize) -> Result<Pubkey> {
+        self.word(index).map(|word| Pubkey::new_from_array(*word))
+    }
+
+    /// Dynamic `bytes` value
+    pub fn bytes(&self, index: usize) -> Result<&'a [u8]> {
+        let offset = self.usize(index)?;
+        let length = self.at(offset)?.usize(0)?;
+
+        self.slice(offset.saturating_add(WORD_SIZE), length)
+    }
+
+    /// Dynamic `string` value
+    pub fn string(&self, index: usize) -> Result<&'a str> {
+        let data = self.bytes(index)?;
+
+        std::str::from_utf8(data)
+            .map_err(|_| E!(ProgramError::InvalidInstructionData; "ABI: invalid utf8 string"))
+    }
+
+    /// Dynamic `T[]` value: the number of elements and the decoder of the elements.
+    /// Static element `i` of `N` words starts at the word `i * N`,
+    /// dynamic element `i` is referenced by the offset in the word `i`.
+    pub fn array(&self, index: usize) -> Result<(usize, Decoder<'a>)> {
+        let offset = self.usize(index)?;
+        let elements = self.at(offset)?;
+        let count = elements.usize(0)?;
+
+        let elements = elements.at(WORD_SIZE)?;
+        if count > elements.data.len() / WORD_SIZE {
+            return Err!(ProgramError::InvalidInstructionData; "ABI: array length {} exceeds input", count);
+        }
+
+        Ok((count, elements))
+    }
+
+    /// Static tuple value of `size` words which starts at the word `index`
+    pub fn static_tuple(&self, index: usize, size: usize) -> Result<Decoder<'a>> {
+        let begin = index.checked_mul(WORD_SIZE)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: argument index {} is too big", index))?;
+        let length = size.checked_mul(WORD_SIZE)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: tuple size {} is too big", size))?;
+
+        self.slice(begin, length).map(Decoder::new)
+    }
+
+    fn at(&self, offset: usize) -> Result<Decoder<'a>> {
+        self.data.get(offset..)
+            .map(Decoder::new)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: offset {} is out of input", offset))
+    }
+
+    fn slice(&self, begin: usize, length: usize) -> Result<&'a [u8]> {
+        let end = begin.checked_add(length)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: length {} is too big", length))?;
+
+        self.data.get(begin..end)
+            .ok_or_else(|| E!(ProgramError::InvalidInstructionData; "ABI: input is too short"))
+    }
+}
+
+
+/// ABI value to encode
+pub enum Token {
+    Uint(U256),
+    Address(H160),
+    Bool(bool),
+    FixedBytes([u8; 32]),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Token>),
+    Tuple(Vec<Token>),
+}
+
+impl Token {
+    #[must_use]
+    pub fn uint(value: impl Into<U256>) -> Self {
+        Self::Uint(value.into())
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Self::Bytes(_) | Self::String(_) | Self::Array(_) => true,
+            Self::Tuple(tokens) => tokens.iter().any(Self::is_dynamic),
+            _ => false,
+        }
+    }
+
+    fn head_size(&self) -> usize {
+        match self {
+            Self::Tuple(tokens) if !self.is_dynamic() => tokens.iter().map(Self::head_size).sum(),
+            _ => WORD_SIZE,
+        }
+    }
+
+    fn encode_into(&self, output: &mut Vec<u8>) {
+        match self {
+            Self::Uint(value) => {
+                let mut word = [0_u8; 32];
+                value.to_big_endian(&mut word);
+                output.extend_from_slice(&word);
+            }
+            Self::Address(address) => {
+                output.extend_from_slice(&[0_u8; 12]);
+                output.extend_from_slice(address.as_bytes());
+            }
+            Self::Bool(value) => {
+                Self::uint(u8::from(*value)).encode_into(output);
+            }
+            Self::FixedBytes(word) => {
+                output.extend_from_slice(word);
+            }
+            Self::Bytes(data) => {
+                encode_bytes(data, output);
+            }
+            Self::String(string) => {
+                encode_bytes(string.as_bytes(), output);
+            }
+            Self::Array(tokens) => {
+                Self::uint(tokens.len()).encode_into(output);
+                output.extend(encode(tokens));
+            }
+            Self::Tuple(tokens) => {
+                output.extend(encode(tokens));
+            }
+        }
+    }
+}
+
+/// Encodes the sequence of values as function arguments or tuple fields
+#[must_use]
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let heads_size: usize = tokens.iter().map(Token::head_size).sum();
+
+    let mut heads = Vec::with_capacity(heads_size);
+    let mut tails = Vec::new();
+
+    for token in tokens {
+        if token.is_dynamic() {
+            Token::uint(heads_size + tails.len()).encode_into(&mut heads);
+            token.encode_into(&mut tails);
+        } else {
+            token.encode_into(&mut heads);
+        }
+    }
+
+    heads.extend(tails);
+    heads
+}
+
+fn encode_bytes(data: &[u8], output: &mut Vec<u8>) {
+    Token::uint(data.len()).encode_into(output);
+    output.extend_from_slice(data);
+
+    let padding = (WORD_SIZE - data.len() % WORD_SIZE) % WORD_SIZE;
+    output.resize(output.len() + padding, 0);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = [0_u8; 32];
+        U256::from(value).to_big_endian(&mut word);
+        word.to_vec()
+    }
+
+    fn padded(data: &[u8]) -> Vec<u8> {
+        let mut word = data.to_vec();
+        word.resize(32, 0);
+        word
+    }
+
+    #[test]
+    fn split_method_selector() {
+        assert!(split_selector(&[0x01, 0x02, 0x03]).is_err());
+
+        let mut input = vec![0x01, 0x02, 0x03, 0x04];
+        input.extend(word(42));
+
+        let (selector, arguments) = split_selector(&input).unwrap();
+        assert_eq!(selector, [0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(arguments.u64(0).unwrap(), 42);
+        assert!(arguments.u64(1).is_err());
+    }
+
+    #[test]
+    fn encode_static_and_dynamic_values() {
+        let output = encode(&[
+            Token::uint(1_u64),
+            Token::Bytes(b"abc".to_vec()),
+            Token::Bool(true),
+        ]);
+
+        let expected = [word(1), word(96), word(1), word(3), padded(b"abc")].concat();
+        assert_eq!(output, expected);
+
+        let decoder = Decoder::new(&output);
+        assert_eq!(decoder.u256(0).unwrap(), U256::one());
+        assert_eq!(decoder.bytes(1).unwrap(), b"abc");
+        assert!(decoder.bool(2).unwrap());
+    }
+
+    #[test]
+    fn encode_static_tuple_in_place() {
+        let address = H160::repeat_byte(0xAA);
+        let output = encode(&[
+            Token::Tuple(vec![Token::uint(7_u64), Token::Address(address)]),
+            Token::String("neon".to_string()),
+        ]);
+
+        let address_word = [[0_u8; 12].as_ref(), address.as_bytes()].concat();
+        let expected = [word(7), address_word, word(96), word(4), padded(b"neon")].concat();
+        assert_eq!(output, expected);
+
+        let decoder = Decoder::new(&output);
+        let tuple = decoder.static_tuple(0, 2).unwrap();
+        assert_eq!(tuple.u64(0).unwrap(), 7);
+        assert_eq!(tuple.address(1).unwrap(), address);
+        assert_eq!(decoder.string(2).unwrap(), "neon");
+    }
+
+    #[test]
+    fn encode_array() {
+        let output = encode(&[Token::Array(vec![Token::uint(5_u64), Token::uint(6_u64)])]);
+        assert_eq!(output, [word(32), word(2), word(5), word(6)].concat());
+
+        let (count, elements) = Decoder::new(&output).array(0).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(elements.u64(0).unwrap(), 5);
+        assert_eq!(elements.u64(1).unwrap(), 6);
+    }
+
+    #[test]
+    fn decode_invalid_values() {
+        let mut dirty_address = word(0);
+        dirty_address[0] = 1;
+        assert!(Decoder::new(&dirty_address).address(0).is_err());
+
+        assert!(Decoder::new(&word(2)).bool(0).is_err());
+        assert!(Decoder::new(&word(256)).u8(0).is_err());
+        assert!(Decoder::new(&[0_u8; 31]).u256(0).is_err());
+        assert!(Decoder::new(&word(0)).u256(usize::MAX).is_err());
+
+        // Offset points outside of the input
+        assert!(Decoder::new(&word(64)).bytes(0).is_err());
+
+        // Length exceeds the input
+        let bytes = [word(32), word(33), padded(b"abc")].concat();
+        assert!(Decoder::new(&bytes).bytes(0).is_err());
+
+        let array = [word(32), word(u64::MAX)].concat();
+        assert!(Decoder::new(&array).array(0).is_err());
+
+        let invalid_utf8 = [word(32), word(1), padded(&[0xFF])].concat();
+        assert!(Decoder::new(&invalid_utf8).string(0).is_err());
+    }
+
+    #[test]
+    fn revert_with_error_string() {
+        let (reason, payload) = match revert("failed") {
+            Capture::Exit(exit) => exit,
+            Capture::Trap(_) => unreachable!(),
+        };
+
+        assert_eq!(reason, ExitReason::Revert(ExitRevert::Reverted));
+        assert_eq!(payload[..4], ERROR_SELECTOR);
+        assert_eq!(payload[4..], [word(32), word(6), padded(b"failed")].concat()[..]);
+
+        let (selector, arguments) = split_selector(&payload).unwrap();
+        assert_eq!(selector, ERROR_SELECTOR);
+        assert_eq!(arguments.string(0).unwrap(), "failed");
+    }
+
+    #[test]
+    fn exit_result() {
+        let success = exit(Ok(word(1)));
+        assert!(matches!(success, Capture::Exit((ExitReason::Succeed(ExitSucceed::Returned), ref output)) if *output == word(1)));
+
+        let failure = exit(Err(ProgramError::InvalidArgument.into()));
+        assert!(matches!(failure, Capture::Exit((ExitReason::Revert(ExitRevert::Reverted), _))));
+
+        let failure = exit(Err!(ProgramError::InvalidArgument; "Account {} - is frozen", 42));
+        let (reason, payload) = match failure {
+            Capture::Exit(exit) => exit,
+            Capture::Trap(_) => unreachable!(),
+        };
+        assert_eq!(reason, ExitReason::Revert(ExitRevert::Reverted));
+        assert_eq!(split_selector(&payload).unwrap().1.string(0).unwrap(), "Account 42 - is frozen");
+    }
+}
@@ -1,6 +1,6 @@
 use std::convert::Infallible;
 
-use evm::{Capture, ExitReason};
+use evm::{Capture, ExitReason, U256};
 
 
 #[must_use]
@@ -61,4 +61,54 @@ pub fn big_mod_exp(
 
     Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), return_value))
     */
+}
+
+/// Gas cost (EIP-2565): `max(200, multiplication_complexity * iteration_count / 3)`.
+/// Input: `base_len (32) || exp_len (32) || mod_len (32) || base || exp || mod`, missing bytes are zeros
+#[must_use]
+pub fn big_mod_exp_gas(input: &[u8]) -> u64 {
+    const MIN_GAS: u64 = 200;
+
+    let word = |offset: usize| -> [u8; 32] {
+        let mut buffer = [0_u8; 32];
+        if let Some(data) = input.get(offset..) {
+            let len = std::cmp::min(data.len(), 32);
+            buffer[..len].copy_from_slice(&data[..len]);
+        }
+        buffer
+    };
+
+    let base_len = U256::from_big_endian(&word(0));
+    let exp_len = U256::from_big_endian(&word(32));
+    let mod_len = U256::from_big_endian(&word(64));
+
+    let max_len = std::cmp::max(base_len, mod_len);
+    if max_len > U256::from(u32::MAX) || exp_len > U256::from(u32::MAX) {
+        return u64::MAX;
+    }
+
+    let max_len = max_len.as_u64();
+    let exp_len = exp_len.as_usize();
+
+    let words = (max_len + 7) / 8;
+    let multiplication_complexity = words * words;
+
+    // Leading 32 bytes of the exponent
+    let exp_head = word(96 + base_len.as_usize());
+    let exp_head = U256::from_big_endian(&exp_head[..std::cmp::min(exp_len, 32)]);
+
+    let exp_head_bits = (exp_head.bits() as u64).saturating_sub(1);
+    let iteration_count = if exp_len <= 32 {
+        exp_head_bits
+    } else {
+        8 * (exp_len as u64 - 32) + exp_head_bits
+    };
+    let iteration_count = std::cmp::max(iteration_count, 1);
+
+    let gas = U256::from(multiplication_complexity) * U256::from(iteration_count) / 3;
+    if gas > U256::from(u64::MAX) {
+        return u64::MAX;
+    }
+
+    std::cmp::max(gas.as_u64(), MIN_GAS)
 }
\ No newline at end of file
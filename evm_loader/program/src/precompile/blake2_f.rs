@@ -137,3 +137,12 @@ pub fn blake2_f(
         output_buf.to_vec(),
     ))
 }
+
+/// Gas cost (EIP-152): one per round, rounds are the first 4 bytes of the input
+#[must_use]
+pub fn blake2_f_gas(input: &[u8]) -> u64 {
+    match input.get(..4) {
+        Some(rounds) => u64::from(u32::from_be_bytes([rounds[0], rounds[1], rounds[2], rounds[3]])),
+        None => 0,
+    }
+}
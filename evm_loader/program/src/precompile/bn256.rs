@@ -269,4 +269,16 @@ pub fn bn256_pairing(
 
     return_val(false)
     */
+}
+
+/// Gas cost of `bn256_add` (EIP-1108)
+pub const BN256_ADD_GAS: u64 = 150;
+
+/// Gas cost of `bn256_scalar_mul` (EIP-1108)
+pub const BN256_SCALAR_MUL_GAS: u64 = 6_000;
+
+/// Gas cost of `bn256_pairing` (EIP-1108): 45000 plus 34000 per pair of points
+#[must_use]
+pub fn bn256_pairing_gas(input: &[u8]) -> u64 {
+    45_000 + 34_000 * (input.len() / 192) as u64
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+use std::convert::Infallible;
+
+use evm::{Capture, ExitReason};
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    account::ACCOUNT_SEED_VERSION,
+    account_storage::AccountStorage,
+    config::{CALL_SOLANA_ALLOWLIST, CALL_SOLANA_DENYLIST},
+    executor::ExecutorState,
+};
+
+use super::abi::{self, Decoder, Token};
+
+// struct AccountMeta { bytes32 pubkey; bool is_signer; bool is_writable; }
+//
+// Returns the return data of the instruction, the instruction is simulated if the program can be emulated.
+//
+// [0xf9, 0xe6, 0x72, 0x9b] : "callSolana(bytes32,(bytes32,bool,bool)[],bytes)"
+// [0x6a, 0xd5, 0xa1, 0x84] : "callSolana(bytes32,(bytes32,bool,bool)[],bytes,uint64)"
+
+#[must_use]
+pub fn call_solana<B: AccountStorage>(
+    input: &[u8],
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    if !context.apparent_value.is_zero() {
+        return abi::revert("callSolana: value transfer is not allowed")
+    }
+
+    if context.address == context.caller {
+        // callcode is not allowed
+        return abi::revert("callSolana: callcode is not allowed")
+    }
+
+    if context.address != super::SYSTEM_ACCOUNT_CALL_SOLANA {
+        // delegatecall is not allowed
+        return abi::revert("callSolana: delegatecall is not allowed")
+    }
+
+    let result = abi::split_selector(input)
+        .and_then(|(selector, arguments)| match selector {
+            [0xf9, 0xe6, 0x72, 0x9b] => { // "callSolana(bytes32,(bytes32,bool,bool)[],bytes)"
+                read_instruction(arguments)
+                    .and_then(|instruction| call(context, state, instruction, 0))
+            }
+            [0x6a, 0xd5, 0xa1, 0x84] => { // "callSolana(bytes32,(bytes32,bool,bool)[],bytes,uint64)"
+                read_instruction(arguments)
+                    .and_then(|instruction| {
+                        let lamports = arguments.u64(3)?;
+                        call(context, state, instruction, lamports)
+                    })
+            }
+            _ => {
+                Err!(ProgramError::InvalidInstructionData; "callSolana: unknown method")
+            }
+        });
+
+    abi::exit(result)
+}
+
+
+fn read_instruction(arguments: Decoder<'_>) -> abi::Result<Instruction> {
+    let program_id = arguments.pubkey(0)?;
+    let accounts = read_account_metas(arguments, 1)?;
+    let data = arguments.bytes(2)?;
+
+    Ok(Instruction { program_id, accounts, data: data.to_vec() })
+}
+
+fn read_account_metas(arguments: Decoder<'_>, index: usize) -> abi::Result<Vec<AccountMeta>> {
+    let (count, elements) = arguments.array(index)?;
+
+    (0..count)
+        .map(|i| {
+            let meta = elements.static_tuple(i * 3, 3)?;
+            Ok(AccountMeta {
+                pubkey: meta.pubkey(0)?,
+                is_signer: meta.bool(1)?,
+                is_writable: meta.bool(2)?,
+            })
+        })
+        .collect()
+}
+
+
+fn is_program_allowed(program_id: &Pubkey) -> bool {
+    if CALL_SOLANA_DENYLIST.binary_search(program_id).is_ok() {
+        return false;
+    }
+
+    CALL_SOLANA_ALLOWLIST.binary_search(program_id).is_ok()
+}
+
+fn call<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    instruction: Instruction,
+    lamports: u64,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    if (&instruction.program_id == state.backend.program_id()) || !is_program_allowed(&instruction.program_id) {
+        return Err!(ProgramError::IncorrectProgramId; "callSolana: program {} is not allowed", instruction.program_id)
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    for meta in &instruction.accounts {
+        // Only the contract's PDA can sign, the operator and other accounts are never passed as signers
+        if meta.is_signer && (meta.pubkey != signer_pubkey) {
+            return Err!(ProgramError::MissingRequiredSignature; "callSolana: account {} can't be a signer", meta.pubkey)
+        }
+    }
+
+    if lamports > 0 {
+        fund_instruction(state, &instruction, lamports)?;
+    }
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+    state.queue_external_instruction(instruction, seeds, 0);
+
+    let return_data = state.external_instructions_return_data()?;
+    Ok(abi::encode(&[Token::Bytes(return_data)]))
+}
+
+/// The operator transfers `lamports` to the first writable account of the instruction,
+/// the same space is reserved as for the account created by `spl_token::create_account`
+fn fund_instruction<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    instruction: &Instruction,
+    lamports: u64,
+) -> abi::Result<()>
+{
+    let rent = Rent::get()?;
+    let max_lamports = rent.minimum_balance(MAX_PERMITTED_DATA_INCREASE);
+    if lamports > max_lamports {
+        return Err!(ProgramError::InvalidArgument; "callSolana: lamports {} exceed limit {}", lamports, max_lamports)
+    }
+
+    let target = instruction.accounts.iter()
+        .find(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey);
+    let target = target.ok_or_else(|| E!(ProgramError::InvalidArgument; "callSolana: no writable account to fund"))?;
+
+    // Smallest space whose rent exemption covers the transferred lamports
+    let lamports_per_byte = rent.minimum_balance(1) - rent.minimum_balance(0);
+    let space = lamports.saturating_sub(rent.minimum_balance(0));
+    let space = (space + lamports_per_byte - 1) / lamports_per_byte;
+
+    let transfer = system_instruction::transfer(state.backend.operator(), &target, lamports);
+    state.queue_external_instruction(transfer, vec![], space.try_into().unwrap());
+
+    Ok(())
+}
@@ -13,4 +13,10 @@ pub fn datacopy(
         ExitReason::Succeed(evm::ExitSucceed::Returned),
         input.to_vec(),
     ))
+}
+
+/// Gas cost: 15 plus 3 per word of input
+#[must_use]
+pub fn datacopy_gas(input: &[u8]) -> u64 {
+    15 + 3 * ((input.len() as u64 + 31) / 32)
 }
\ No newline at end of file
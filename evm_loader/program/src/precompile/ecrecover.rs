@@ -6,6 +6,8 @@ use solana_program::secp256k1_recover::secp256k1_recover;
 
 use crate::utils::keccak256_digest;
 
+/// Gas cost of `ecrecover`
+pub const ECRECOVER_GAS: u64 = 3_000;
 
 #[must_use]
 pub fn ecrecover(
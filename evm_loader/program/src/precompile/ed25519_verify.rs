@@ -0,0 +1,47 @@
+use std::convert::Infallible;
+
+use arrayref::array_ref;
+use evm::{Capture, ExitReason};
+use solana_program::program_error::ProgramError;
+
+use crate::{account_storage::AccountStorage, executor::ExecutorState};
+
+use super::abi::{self, Decoder, Token};
+
+// "[0x46, 0x8b, 0x95, 0x92]": "ed25519Verify(bytes32,bytes,bytes)"
+
+#[must_use]
+pub fn ed25519_verify<B: AccountStorage>(
+    input: &[u8],
+    state: &ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    debug_print!("ed25519_verify({})", hex::encode(input));
+
+    let result = abi::split_selector(input)
+        .and_then(|(selector, arguments)| match selector {
+            [0x46, 0x8b, 0x95, 0x92] => { // "ed25519Verify(bytes32,bytes,bytes)"
+                read_arguments(arguments)
+                    .map(|(pubkey, message, signature)| {
+                        let verified = signature.len() == 64
+                            && state.backend.ed25519_verify(&pubkey, message, array_ref![signature, 0, 64]);
+
+                        abi::encode(&[Token::Bool(verified)])
+                    })
+            }
+            _ => {
+                Err!(ProgramError::InvalidInstructionData; "ed25519Verify: unknown method")
+            }
+        });
+
+    abi::exit(result)
+}
+
+
+fn read_arguments(arguments: Decoder<'_>) -> abi::Result<([u8; 32], &[u8], &[u8])> {
+    let pubkey = arguments.bytes32(0)?;
+    let message = arguments.bytes(1)?;
+    let signature = arguments.bytes(2)?;
+
+    Ok((pubkey, message, signature))
+}
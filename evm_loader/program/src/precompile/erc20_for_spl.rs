@@ -0,0 +1,421 @@
+use std::convert::{Infallible, TryInto};
+
+use evm::{Capture, ExitReason, H160, H256, U256};
+use solana_program::{keccak, program_error::ProgramError, program_option::COption, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::StateWithExtensions;
+
+use crate::{
+    account::ACCOUNT_SEED_VERSION,
+    account_storage::AccountStorage,
+    executor::ExecutorState,
+};
+
+use super::abi::{self, Token};
+use super::spl_token::{associated_token_account, create_account, token_account_space, token_mint};
+
+// ERC-20 interface of the registered SPL mint, `ERC20_FOR_SPL_PREFIX` addresses:
+//
+// [0x06, 0xfd, 0xde, 0x03] : "name()"
+// [0x95, 0xd8, 0x9b, 0x41] : "symbol()"
+// [0x31, 0x3c, 0xe5, 0x67] : "decimals()"
+// [0x18, 0x16, 0x0d, 0xdd] : "totalSupply()"
+// [0x70, 0xa0, 0x82, 0x31] : "balanceOf(address)"
+// [0xa9, 0x05, 0x9c, 0xbb] : "transfer(address,uint256)"
+// [0x23, 0xb8, 0x72, 0xdd] : "transferFrom(address,address,uint256)"
+// [0x09, 0x5e, 0xa7, 0xb3] : "approve(address,uint256)"
+// [0xdd, 0x62, 0xed, 0x3e] : "allowance(address,address)"
+// [0xaf, 0x25, 0xc0, 0x77] : "tokenMint()"
+//
+// Factory, `SYSTEM_ACCOUNT_ERC20_FOR_SPL_FACTORY` address:
+//
+// [0xe1, 0xfa, 0x8e, 0x84] : "register(bytes32)"
+// [0x21, 0xf8, 0xa7, 0x21] : "getAddress(bytes32)"
+//
+// Balances are kept in the associated token accounts of the Neon accounts' Solana addresses.
+// SPL Token supports a single delegate per account: `approve` replaces the previous spender.
+
+/// ERC-20 addresses of SPL mints start with this prefix, the rest is the hash of the mint.
+/// The prefix is short enough to grind an address into the range, so the whole range is reserved:
+/// contracts can't be deployed to it and transactions from it are rejected.
+pub const ERC20_FOR_SPL_PREFIX: [u8; 4] = [0xff, 0x00, 0x00, 0x20];
+
+const TRANSFER_EVENT_TOPIC: H256 = H256([
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+]);
+
+const APPROVAL_EVENT_TOPIC: H256 = H256([
+    0x8c, 0x5b, 0xe1, 0xe5, 0xeb, 0xec, 0x7d, 0x5b, 0xd1, 0x4f, 0x71, 0x42, 0x7d, 0x1e, 0x84, 0xf3,
+    0xdd, 0x03, 0x14, 0xc0, 0xf7, 0xb2, 0x29, 0x1e, 0x5b, 0x20, 0x0a, 0xc8, 0xc7, 0xc3, 0xb9, 0x25,
+]);
+
+/// Is the address in the range of the ERC-20 interfaces of SPL mints
+#[must_use]
+pub fn is_erc20_for_spl_address(address: &H160) -> bool {
+    address.as_bytes().starts_with(&ERC20_FOR_SPL_PREFIX)
+}
+
+/// ERC-20 address of the SPL mint
+#[must_use]
+pub fn erc20_for_spl_address(mint: &Pubkey) -> H160 {
+    let hash = keccak::hash(mint.as_ref());
+
+    let mut address = [0_u8; 20];
+    address[..4].copy_from_slice(&ERC20_FOR_SPL_PREFIX);
+    address[4..].copy_from_slice(&hash.as_ref()[16..]);
+
+    H160(address)
+}
+
+/// The registry token account of the ERC-20 address, its `mint` field links the address to the mint
+fn registry_address(program_id: &Pubkey, address: &H160) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&[ACCOUNT_SEED_VERSION], b"ERC20ForSpl", address.as_bytes()], program_id)
+}
+
+
+#[must_use]
+pub fn erc20_for_spl<B: AccountStorage>(
+    input: &[u8],
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    if !context.apparent_value.is_zero() {
+        return abi::revert("ERC20ForSpl: value transfer is not allowed")
+    }
+
+    if context.address == context.caller {
+        // callcode is not allowed
+        return abi::revert("ERC20ForSpl: callcode is not allowed")
+    }
+
+    if !is_erc20_for_spl_address(&context.address) {
+        // delegatecall is not allowed
+        return abi::revert("ERC20ForSpl: delegatecall is not allowed")
+    }
+
+    let result = abi::split_selector(input).and_then(|(selector, input)| {
+        let (token_program, mint) = registered_mint(state, &context.address)?;
+
+        match selector {
+            [0x06, 0xfd, 0xde, 0x03] => { // "name()"
+                name(context, state, mint)
+            }
+            [0x95, 0xd8, 0x9b, 0x41] => { // "symbol()"
+                symbol(context, state, mint)
+            }
+            [0x31, 0x3c, 0xe5, 0x67] => { // "decimals()"
+                let (_, mint) = token_mint(state, mint)?;
+                Ok(abi::encode(&[Token::uint(mint.decimals)]))
+            }
+            [0x18, 0x16, 0x0d, 0xdd] => { // "totalSupply()"
+                let (_, mint) = token_mint(state, mint)?;
+                Ok(abi::encode(&[Token::uint(mint.supply)]))
+            }
+            [0x70, 0xa0, 0x82, 0x31] => { // "balanceOf(address)"
+                let owner = input.address(0)?;
+                balance_of(state, &token_program, &mint, &owner)
+            }
+            [0xa9, 0x05, 0x9c, 0xbb] => { // "transfer(address,uint256)"
+                let target = input.address(0)?;
+                let amount = input.u64(1)?;
+                transfer(context, state, mint, context.caller, target, amount)
+            }
+            [0x23, 0xb8, 0x72, 0xdd] => { // "transferFrom(address,address,uint256)"
+                let source = input.address(0)?;
+                let target = input.address(1)?;
+                let amount = input.u64(2)?;
+                transfer(context, state, mint, source, target, amount)
+            }
+            [0x09, 0x5e, 0xa7, 0xb3] => { // "approve(address,uint256)"
+                let spender = input.address(0)?;
+                let amount = input.u256(1)?;
+                approve(context, state, mint, spender, amount)
+            }
+            [0xdd, 0x62, 0xed, 0x3e] => { // "allowance(address,address)"
+                let owner = input.address(0)?;
+                let spender = input.address(1)?;
+                allowance(state, &token_program, &mint, &owner, &spender)
+            }
+            [0xaf, 0x25, 0xc0, 0x77] => { // "tokenMint()"
+                Ok(abi::encode(&[Token::FixedBytes(mint.to_bytes())]))
+            }
+            _ => {
+                Err!(ProgramError::InvalidInstructionData; "ERC20ForSpl: unknown method")
+            }
+        }
+    });
+
+    abi::exit(result)
+}
+
+#[must_use]
+pub fn factory<B: AccountStorage>(
+    input: &[u8],
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    if !context.apparent_value.is_zero() {
+        return abi::revert("ERC20ForSpl: value transfer is not allowed")
+    }
+
+    let result = abi::split_selector(input).and_then(|(selector, input)| match selector {
+        [0xe1, 0xfa, 0x8e, 0x84] => { // "register(bytes32)"
+            input.pubkey(0)
+                .and_then(|mint| register(state, mint))
+        }
+        [0x21, 0xf8, 0xa7, 0x21] => { // "getAddress(bytes32)"
+            input.pubkey(0)
+                .map(|mint| abi::encode(&[Token::Address(erc20_for_spl_address(&mint))]))
+        }
+        _ => {
+            Err!(ProgramError::InvalidInstructionData; "ERC20ForSpl: unknown method")
+        }
+    });
+
+    abi::exit(result)
+}
+
+
+/// Links the ERC-20 address to the mint. Anyone can register a mint, the operator pays for the registry account.
+fn register<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let address = erc20_for_spl_address(&mint);
+    let (registry_key, bump_seed) = registry_address(state.backend.program_id(), &address);
+
+    let mint_account = state.external_account(mint)?;
+    spl_token_2022::check_spl_token_program_account(&mint_account.owner)?;
+
+    let token_program = mint_account.owner;
+
+    let account = state.external_account(registry_key)?;
+    if account.owner != token_program {
+        if !solana_program::system_program::check_id(&account.owner) {
+            return Err!(ProgramError::IllegalOwner; "Account {} - is not system owned", registry_key);
+        }
+
+        let seeds: Vec<Vec<u8>> = vec![
+            vec![ACCOUNT_SEED_VERSION], b"ERC20ForSpl".to_vec(),
+            address.as_bytes().to_vec(),
+            vec![bump_seed]
+        ];
+
+        let space = token_account_space(&mint_account, &[])?;
+        create_account(state, &account, &token_program, space, seeds)?;
+
+        let initialize_account = spl_token_2022::instruction::initialize_account3(
+            &token_program,
+            &registry_key,
+            &mint,
+            &registry_key
+        )?;
+        state.queue_external_instruction(initialize_account, vec![], 0);
+    }
+
+    Ok(abi::encode(&[Token::Address(address)]))
+}
+
+/// Returns the token program and the mint registered for the ERC-20 address
+fn registered_mint<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    address: &H160,
+) -> abi::Result<(Pubkey, Pubkey)>
+{
+    let (registry_key, _) = registry_address(state.backend.program_id(), address);
+
+    let account = state.external_account(registry_key)?;
+    if spl_token_2022::check_spl_token_program_account(&account.owner).is_err() {
+        return Err!(ProgramError::UninitializedAccount; "ERC20ForSpl: address {} is not registered", address);
+    }
+
+    let mint = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)?.base.mint;
+    Ok((account.owner, mint))
+}
+
+fn balance_of<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &H160,
+) -> abi::Result<Vec<u8>>
+{
+    let balance = token_account(state, token_program, mint, owner)?
+        .map_or(0, |account| account.amount);
+
+    Ok(abi::encode(&[Token::uint(balance)]))
+}
+
+fn allowance<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &H160,
+    spender: &H160,
+) -> abi::Result<Vec<u8>>
+{
+    let (spender_pubkey, _) = state.backend.solana_address(spender);
+
+    let allowance = token_account(state, token_program, mint, owner)?
+        .filter(|account| account.delegate == COption::Some(spender_pubkey))
+        .map_or(0, |account| account.delegated_amount);
+
+    Ok(abi::encode(&[Token::uint(allowance)]))
+}
+
+/// Moves tokens between the associated token accounts.
+/// The caller signs as the owner of the source account, or as its delegate.
+/// `Transfer` event reports the amount received by the target, transfer fee of Token-2022 mint excluded.
+fn transfer<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+    source: H160,
+    target: H160,
+    amount: u64,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let (source_pubkey, _) = state.backend.solana_address(&source);
+    let (target_pubkey, _) = state.backend.solana_address(&target);
+
+    let (token_program, mint_state) = token_mint(state, mint)?;
+
+    let source_account = get_associated_token_address_with_program_id(&source_pubkey, &mint, &token_program);
+    let target_account = associated_token_account(state, target_pubkey, mint)?;
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let target_balance = token_account(state, &token_program, &mint, &target)?
+        .map_or(0, |account| account.amount);
+
+    let transfer = spl_token_2022::instruction::transfer_checked(
+        &token_program,
+        &source_account,
+        &mint,
+        &target_account,
+        &signer_pubkey,
+        &[],
+        amount,
+        mint_state.decimals
+    )?;
+    state.queue_external_instruction(transfer, seeds, 0);
+
+    // Fails the call right away if the balance or the allowance is insufficient
+    state.external_instructions_return_data()?;
+
+    let received = if source_account == target_account {
+        amount
+    } else {
+        token_account(state, &token_program, &mint, &target)?
+            .map_or(0, |account| account.amount)
+            .saturating_sub(target_balance)
+    };
+
+    let topics = vec![TRANSFER_EVENT_TOPIC, H256::from(source), H256::from(target)];
+    state.log(context.address, topics, abi::encode(&[Token::uint(received)]));
+
+    Ok(abi::encode(&[Token::Bool(true)]))
+}
+
+/// Sets the spender as the delegate of the caller's associated token account.
+/// The amount is capped to `u64::MAX`, so the "infinite" approvals are supported.
+fn approve<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+    spender: H160,
+    value: U256,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+    let (spender_pubkey, _) = state.backend.solana_address(&spender);
+
+    let (token_program, mint_data) = token_mint(state, mint)?;
+    let source_account = associated_token_account(state, signer_pubkey, mint)?;
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let amount: u64 = value.try_into().unwrap_or(u64::MAX);
+    let approve = spl_token_2022::instruction::approve_checked(
+        &token_program,
+        &source_account,
+        &mint,
+        &spender_pubkey,
+        &signer_pubkey,
+        &[],
+        amount,
+        mint_data.decimals
+    )?;
+    state.queue_external_instruction(approve, seeds, 0);
+
+    let topics = vec![APPROVAL_EVENT_TOPIC, H256::from(signer), H256::from(spender)];
+    // The delegated amount is capped by u64, the event reports the actual allowance
+    state.log(context.address, topics, abi::encode(&[Token::uint(amount)]));
+
+    Ok(abi::encode(&[Token::Bool(true)]))
+}
+
+/// Returns the associated token account of the Neon account, `None` if it doesn't exist
+fn token_account<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &H160,
+) -> abi::Result<Option<spl_token_2022::state::Account>>
+{
+    let (owner_pubkey, _) = state.backend.solana_address(owner);
+    let address = get_associated_token_address_with_program_id(&owner_pubkey, mint, token_program);
+
+    let account = state.external_account(address)?;
+    if &account.owner != token_program {
+        return Ok(None);
+    }
+
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)?.base;
+    Ok(Some(token_account))
+}
+
+fn name<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let name = super::metaplex::metadata(context, state, mint)?
+        .map_or_else(String::new, |m| m.data.name);
+
+    Ok(abi::encode(&[Token::String(name.trim_end_matches('\0').to_string())]))
+}
+
+fn symbol<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let symbol = super::metaplex::metadata(context, state, mint)?
+        .map_or_else(String::new, |m| m.data.symbol);
+
+    Ok(abi::encode(&[Token::String(symbol.trim_end_matches('\0').to_string())]))
+}
+
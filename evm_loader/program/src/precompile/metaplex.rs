@@ -1,8 +1,8 @@
 use std::convert::{Infallible};
 
-use evm::{Capture, ExitReason, U256, ExitSucceed, ExitRevert};
+use evm::{Capture, ExitReason};
 use solana_program::{program_error::ProgramError, pubkey::Pubkey};
-use mpl_token_metadata::state::{Creator, Metadata, TokenStandard, TokenMetadataAccount};
+use mpl_token_metadata::state::{Creator, DataV2, Metadata, TokenStandard, TokenMetadataAccount};
 
 
 use crate::{
@@ -10,13 +10,24 @@ use crate::{
     executor::{ExecutorState}, account::ACCOUNT_SEED_VERSION,
 };
 
+use super::abi::{self, Token};
+
 // "[0xc5, 0x73, 0x50, 0xc6]": "createMetadata(bytes32,string,string,string)"
+// "[0x5d, 0x41, 0x72, 0xf9]": "createMetadata(bytes32,string,string,string,bool)"
 // "[0x4a, 0xe8, 0xb6, 0x6b]": "createMasterEdition(bytes32,uint64)"
 // "[0xf7, 0xb6, 0x37, 0xbb]": "isInitialized(bytes32)"
 // "[0x23, 0x5b, 0x2b, 0x94]": "isNFT(bytes32)"
 // "[0x9e, 0xd1, 0x9d, 0xdb]": "uri(bytes32)"
 // "[0x69, 0x1f, 0x34, 0x31]": "name(bytes32)"
 // "[0x6b, 0xaa, 0x03, 0x30]": "symbol(bytes32)"
+// "[0x27, 0xb4, 0x4b, 0x02]": "updateMetadata(bytes32,string,string,string,uint16)"
+// "[0x87, 0xca, 0x18, 0xa6]": "setCollection(bytes32,bytes32)"
+// "[0x89, 0xf3, 0x5d, 0xd1]": "verifyCollection(bytes32,bytes32)"
+// "[0x9b, 0xf3, 0xb7, 0x3f]": "signMetadata(bytes32)"
+// "[0x47, 0x7f, 0x75, 0x14]": "printEdition(bytes32,bytes32,bytes32,uint64)"
+// "[0x73, 0x57, 0x4e, 0x36]": "sellerFeeBasisPoints(bytes32)"
+// "[0x9e, 0xc2, 0x1d, 0xc2]": "creators(bytes32)"
+// "[0x36, 0xa6, 0x80, 0x2f]": "collection(bytes32)"
 
 #[must_use]
 pub fn metaplex<B: AccountStorage>(
@@ -26,89 +37,115 @@ pub fn metaplex<B: AccountStorage>(
 ) -> Capture<(ExitReason, Vec<u8>), Infallible>
 {
     if !context.apparent_value.is_zero() {
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("Metaplex: value transfer is not allowed")
     }
 
     if context.address == context.caller {
         // callcode is not allowed
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("Metaplex: callcode is not allowed")
     }
 
     if context.address != super::SYSTEM_ACCOUNT_METAPLEX {
         // delegatecall is not allowed
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("Metaplex: delegatecall is not allowed")
     }
 
 
-    let (selector, input) = input.split_at(4);
-    let result = match *selector {
+    let result = abi::split_selector(input).and_then(|(selector, input)| match selector {
         [0xc5, 0x73, 0x50, 0xc6] => { // "createMetadata(bytes32,string,string,string)"
-            let mint = read_pubkey(input);
-            let name = read_string(input, 32);
-            let symbol = read_string(input, 64);
-            let uri = read_string(input, 96);
+            let mint = input.pubkey(0)?;
+            let name = input.string(1)?.to_string();
+            let symbol = input.string(2)?.to_string();
+            let uri = input.string(3)?.to_string();
 
-            create_metadata(context, state, mint, name, symbol, uri)
+            create_metadata(context, state, mint, name, symbol, uri, false)
+        }
+        [0x5d, 0x41, 0x72, 0xf9] => { // "createMetadata(bytes32,string,string,string,bool)"
+            let mint = input.pubkey(0)?;
+            let name = input.string(1)?.to_string();
+            let symbol = input.string(2)?.to_string();
+            let uri = input.string(3)?.to_string();
+            let is_mutable = input.bool(4)?;
+
+            create_metadata(context, state, mint, name, symbol, uri, is_mutable)
         }
         [0x4a, 0xe8, 0xb6, 0x6b] => { // "createMasterEdition(bytes32,uint64)"
-            let mint = read_pubkey(input);
-            let max_supply = read_u64(&input[32..]);
+            let mint = input.pubkey(0)?;
+            let max_supply = input.u64(1)?;
 
             create_master_edition(context, state, mint, Some(max_supply))
         }
         [0xf7, 0xb6, 0x37, 0xbb] => { // "isInitialized(bytes32)"
-            let mint = read_pubkey(input);
+            let mint = input.pubkey(0)?;
             is_initialized(context, state, mint)
         } 
         [0x23, 0x5b, 0x2b, 0x94] => { // "isNFT(bytes32)"
-            let mint = read_pubkey(input);
+            let mint = input.pubkey(0)?;
             is_nft(context, state, mint)
         }
         [0x9e, 0xd1, 0x9d, 0xdb] => { // "uri(bytes32)"
-            let mint = read_pubkey(input);
+            let mint = input.pubkey(0)?;
             uri(context, state, mint)
         }
         [0x69, 0x1f, 0x34, 0x31] => { // "name(bytes32)"
-            let mint = read_pubkey(input);
+            let mint = input.pubkey(0)?;
             token_name(context, state, mint)
         }
         [0x6b, 0xaa, 0x03, 0x30] => { // "symbol(bytes32)"
-            let mint = read_pubkey(input);
+            let mint = input.pubkey(0)?;
             symbol(context, state, mint)
         }
-        _ => {
-            Ok(vec![])
+        [0x27, 0xb4, 0x4b, 0x02] => { // "updateMetadata(bytes32,string,string,string,uint16)"
+            let mint = input.pubkey(0)?;
+            let name = input.string(1)?.to_string();
+            let symbol = input.string(2)?.to_string();
+            let uri = input.string(3)?.to_string();
+            let seller_fee_basis_points = input.u16(4)?;
+
+            update_metadata(context, state, mint, name, symbol, uri, seller_fee_basis_points)
         }
-    };
-
+        [0x87, 0xca, 0x18, 0xa6] => { // "setCollection(bytes32,bytes32)"
+            let mint = input.pubkey(0)?;
+            let collection_mint = input.pubkey(1)?;
 
-    result.map_or_else(
-        |_| Capture::Exit((ExitRevert::Reverted.into(), vec![])),
-        |value| Capture::Exit((ExitSucceed::Returned.into(), value))
-    )
-}
-
-
-#[inline]
-fn read_u64(input: &[u8]) -> u64 {
-    U256::from_big_endian_fast(arrayref::array_ref![input, 0, 32]).as_u64()
-}
-
-#[inline]
-fn read_pubkey(input: &[u8]) -> Pubkey {
-    Pubkey::new_from_array(*arrayref::array_ref![input, 0, 32])
-}
+            set_collection(context, state, mint, collection_mint)
+        }
+        [0x89, 0xf3, 0x5d, 0xd1] => { // "verifyCollection(bytes32,bytes32)"
+            let mint = input.pubkey(0)?;
+            let collection_mint = input.pubkey(1)?;
 
-#[inline]
-fn read_string(input: &[u8], offset_position: usize) -> String {
-    let offset = U256::from_big_endian_fast(arrayref::array_ref![input, offset_position, 32]).as_usize();
-    let length = U256::from_big_endian_fast(arrayref::array_ref![input, offset, 32]).as_usize();
+            verify_collection(context, state, mint, collection_mint)
+        }
+        [0x9b, 0xf3, 0xb7, 0x3f] => { // "signMetadata(bytes32)"
+            let mint = input.pubkey(0)?;
+            sign_metadata(context, state, mint)
+        }
+        [0x47, 0x7f, 0x75, 0x14] => { // "printEdition(bytes32,bytes32,bytes32,uint64)"
+            let master_mint = input.pubkey(0)?;
+            let new_mint = input.pubkey(1)?;
+            let token_account = input.pubkey(2)?;
+            let edition = input.u64(3)?;
 
-    let begin = offset + 32;
-    let end = begin + length;
+            print_edition(context, state, master_mint, new_mint, token_account, edition)
+        }
+        [0x73, 0x57, 0x4e, 0x36] => { // "sellerFeeBasisPoints(bytes32)"
+            let mint = input.pubkey(0)?;
+            seller_fee_basis_points(context, state, mint)
+        }
+        [0x9e, 0xc2, 0x1d, 0xc2] => { // "creators(bytes32)"
+            let mint = input.pubkey(0)?;
+            creators(context, state, mint)
+        }
+        [0x36, 0xa6, 0x80, 0x2f] => { // "collection(bytes32)"
+            let mint = input.pubkey(0)?;
+            collection(context, state, mint)
+        }
+        _ => {
+            Err!(ProgramError::InvalidInstructionData; "Metaplex: unknown method")
+        }
+    });
 
-    let data = input[begin..end].to_vec();
-    unsafe { String::from_utf8_unchecked(data) }
+    abi::exit(result)
 }
 
 
@@ -119,7 +156,8 @@ fn create_metadata<B: AccountStorage>(
     name: String,
     symbol: String,
     uri: String,
-) -> Result<Vec<u8>, ProgramError>
+    is_mutable: bool,
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -148,14 +186,14 @@ fn create_metadata<B: AccountStorage>(
         ]),
         0,     // Seller Fee
         true,  // Update Authority == Mint Authority
-        false, // Is Mutable
+        is_mutable,
         None,  // Collection
         None,  // Uses
         None,  // Collection Details
     );
     state.queue_external_instruction(instruction, seeds, mpl_token_metadata::state::MAX_METADATA_LEN);
 
-    Ok(metadata_pubkey.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(metadata_pubkey.to_bytes())]))
 }
 
 
@@ -164,7 +202,7 @@ fn create_master_edition<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     mint: Pubkey,
     max_supply: Option<u64>,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -190,73 +228,315 @@ fn create_master_edition<B: AccountStorage>(
     );
     state.queue_external_instruction(instruction, seeds, mpl_token_metadata::state::MAX_MASTER_EDITION_LEN);
 
-    Ok(edition_pubkey.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(edition_pubkey.to_bytes())]))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_metadata<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let metadata = metadata(context, state, mint)?
+        .ok_or_else(|| E!(ProgramError::UninitializedAccount; "Metaplex: metadata for mint {} is not initialized", mint))?;
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let (metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
+
+    let data = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points,
+        creators: metadata.data.creators,
+        collection: metadata.collection,
+        uses: metadata.uses,
+    };
+
+    let instruction = mpl_token_metadata::instruction::update_metadata_accounts_v2(
+        mpl_token_metadata::ID,
+        metadata_pubkey,
+        signer_pubkey,
+        None,       // New Update Authority
+        Some(data),
+        None,       // Primary Sale Happened
+        None,       // Is Mutable
+    );
+    state.queue_external_instruction(instruction, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn set_collection<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+    collection_mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let (metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
+    let (collection_metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&collection_mint);
+    let (collection_edition_pubkey, _) = mpl_token_metadata::pda::find_master_edition_account(&collection_mint);
+
+    let instruction = mpl_token_metadata::instruction::set_and_verify_collection(
+        mpl_token_metadata::ID,
+        metadata_pubkey,
+        signer_pubkey,
+        *state.backend.operator(),
+        signer_pubkey,
+        collection_mint,
+        collection_metadata_pubkey,
+        collection_edition_pubkey,
+        None, // Collection Authority Record
+    );
+    state.queue_external_instruction(instruction, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn verify_collection<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+    collection_mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let (metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
+    let (collection_metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&collection_mint);
+    let (collection_edition_pubkey, _) = mpl_token_metadata::pda::find_master_edition_account(&collection_mint);
+
+    let instruction = mpl_token_metadata::instruction::verify_collection(
+        mpl_token_metadata::ID,
+        metadata_pubkey,
+        signer_pubkey,
+        *state.backend.operator(),
+        collection_mint,
+        collection_metadata_pubkey,
+        collection_edition_pubkey,
+        None, // Collection Authority Record
+    );
+    state.queue_external_instruction(instruction, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn sign_metadata<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let (metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
+
+    let instruction = mpl_token_metadata::instruction::sign_metadata(
+        mpl_token_metadata::ID,
+        metadata_pubkey,
+        signer_pubkey,
+    );
+    state.queue_external_instruction(instruction, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn print_edition<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    master_mint: Pubkey,
+    new_mint: Pubkey,
+    token_account: Pubkey,
+    edition: u64,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let (master_metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&master_mint);
+    let (master_edition_pubkey, _) = mpl_token_metadata::pda::find_master_edition_account(&master_mint);
+    let (new_metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&new_mint);
+    let (new_edition_pubkey, _) = mpl_token_metadata::pda::find_master_edition_account(&new_mint);
+
+    let instruction = mpl_token_metadata::instruction::mint_new_edition_from_master_edition_via_token(
+        mpl_token_metadata::ID,
+        new_metadata_pubkey,
+        new_edition_pubkey,
+        master_edition_pubkey,
+        new_mint,
+        signer_pubkey,
+        *state.backend.operator(),
+        signer_pubkey,
+        token_account,
+        signer_pubkey,
+        master_metadata_pubkey,
+        master_mint,
+        edition,
+    );
+
+    let allocate = mpl_token_metadata::state::MAX_METADATA_LEN
+        + mpl_token_metadata::state::MAX_EDITION_LEN
+        + mpl_token_metadata::state::MAX_EDITION_MARKER_SIZE;
+    state.queue_external_instruction(instruction, seeds, allocate);
+
+    Ok(abi::encode(&[Token::FixedBytes(new_edition_pubkey.to_bytes())]))
 }
 
 fn is_initialized<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let is_initialized = metadata(context, state, mint)?
         .map_or_else(|| false, |_| true);
 
-    Ok(to_solidity_bool(is_initialized))
+    Ok(abi::encode(&[Token::Bool(is_initialized)]))
 }
 
 fn is_nft<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let is_nft = metadata(context, state, mint)?
         .map_or_else(|| false, |m| m.token_standard == Some(TokenStandard::NonFungible));
 
-    Ok(to_solidity_bool(is_nft))
+    Ok(abi::encode(&[Token::Bool(is_nft)]))
 }
 
 fn uri<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let uri = metadata(context, state, mint)?
         .map_or_else(String::new, |m| m.data.uri);
 
-    Ok(to_solidity_string(uri.trim_end_matches('\0')))
+    Ok(abi::encode(&[Token::String(uri.trim_end_matches('\0').to_string())]))
 }
 
 fn token_name<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let token_name = metadata(context, state, mint)?
         .map_or_else(String::new, |m| m.data.name);
 
-    Ok(to_solidity_string(token_name.trim_end_matches('\0')))
+    Ok(abi::encode(&[Token::String(token_name.trim_end_matches('\0').to_string())]))
 }
 
 fn symbol<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let symbol = metadata(context, state, mint)?
         .map_or_else(String::new, |m| m.data.symbol);
 
-    Ok(to_solidity_string(symbol.trim_end_matches('\0')))
+    Ok(abi::encode(&[Token::String(symbol.trim_end_matches('\0').to_string())]))
 }
 
-fn metadata<B: AccountStorage>(
+fn seller_fee_basis_points<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let seller_fee_basis_points = metadata(context, state, mint)?
+        .map_or(0, |m| m.data.seller_fee_basis_points);
+
+    Ok(abi::encode(&[Token::uint(seller_fee_basis_points)]))
+}
+
+fn creators<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let creators = metadata(context, state, mint)?
+        .and_then(|m| m.data.creators)
+        .unwrap_or_default();
+
+    // Array of (bytes32 creator, bool verified, uint8 share) tuples
+    let creators = creators.iter()
+        .map(|creator| Token::Tuple(vec![
+            Token::FixedBytes(creator.address.to_bytes()),
+            Token::Bool(creator.verified),
+            Token::uint(creator.share),
+        ]))
+        .collect();
+
+    Ok(abi::encode(&[Token::Array(creators)]))
+}
+
+fn collection<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let collection = metadata(context, state, mint)?
+        .and_then(|m| m.collection);
+
+    let (key, verified) = collection.map_or(([0_u8; 32], false), |c| (c.key.to_bytes(), c.verified));
+
+    Ok(abi::encode(&[Token::FixedBytes(key), Token::Bool(verified)]))
+}
+
+pub(super) fn metadata<B: AccountStorage>(
     _context: &evm::Context,
     state: &mut ExecutorState<B>,
     mint: Pubkey,
-) -> Result<Option<Metadata>, ProgramError>
+) -> abi::Result<Option<Metadata>>
 {
     let (metadata_pubkey, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
     let metadata_account = state.external_account(metadata_pubkey)?;
@@ -271,35 +551,3 @@ fn metadata<B: AccountStorage>(
     };
     Ok(result)
 }
-
-fn to_solidity_bool(v: bool) -> Vec<u8>
-{
-    let mut result = vec![0_u8; 32];
-    result[31] = u8::from(v);
-    result
-}
-
-fn to_solidity_string(s: &str) -> Vec<u8>
-{
-    // String encoding
-    // 32 bytes - offset
-    // 32 bytes - length
-    // length + padding bytes - data
-
-    let data_len = if s.len() % 32 == 0 {
-        std::cmp::max(s.len(), 32)
-    } else {
-        ((s.len() / 32) + 1) * 32
-    };
-
-    let mut result = vec![0_u8; 32 + 32 + data_len];
-
-    result[31] = 0x20; // offset - 32 bytes
-
-    let length = U256::from(s.len());
-    length.into_big_endian_fast(&mut result[32..64]);
-    
-    result[64..64 + s.len()].copy_from_slice(s.as_bytes());
-
-    result
-}
\ No newline at end of file
@@ -5,18 +5,51 @@ use evm::{H160, Capture, ExitReason};
 use crate::{account_storage::AccountStorage, executor::ExecutorState};
 
 
+/// Same as the crate `E!`, but the message is kept in `abi::Error`
+/// and returned to the contract in the `Error(string)` revert payload
+macro_rules! E {
+    ( $n:expr; $($args:expr),* ) => ({
+        let error: solana_program::program_error::ProgramError = $n;
+        let message = format!($($args),*);
+
+        #[cfg(target_arch = "bpf")]
+        solana_program::msg!("{}:{} : {} : {}", file!(), line!(), error, &message);
+
+        #[cfg(not(target_arch = "bpf"))]
+        log::error!("{} : {}", error, &message);
+
+        crate::precompile::abi::Error { message }
+    });
+}
+
+/// Same as the crate `Err!`, but the message is kept in `abi::Error`
+macro_rules! Err {
+    ( $n:expr; $($args:expr),* ) => ( Err(E!($n; $($args),*)) );
+}
+
+
 mod ecrecover;
+mod ed25519_verify;
 mod sha256;
 mod ripemd160;
 mod datacopy;
 mod big_mod_exp;
 mod bn256;
 mod blake2_f;
+mod p256_verify;
 
 mod query_account;
 mod neon_token;
 mod spl_token;
 mod metaplex;
+mod call_solana;
+mod solana_utils;
+mod erc20_for_spl;
+mod wsol;
+
+mod abi;
+
+pub use erc20_for_spl::is_erc20_for_spl_address;
 
 #[deprecated]
 const _SYSTEM_ACCOUNT_ERC20_WRAPPER: H160 =     H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]);
@@ -25,6 +58,11 @@ const SYSTEM_ACCOUNT_QUERY: H160 =             H160([0xff, 0, 0, 0, 0, 0, 0, 0,
 const SYSTEM_ACCOUNT_NEON_TOKEN: H160 =        H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x03]);
 const SYSTEM_ACCOUNT_SPL_TOKEN: H160 =         H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x04]);
 const SYSTEM_ACCOUNT_METAPLEX: H160 =          H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x05]);
+const SYSTEM_ACCOUNT_ED25519_VERIFY: H160 =    H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x06]);
+const SYSTEM_ACCOUNT_CALL_SOLANA: H160 =       H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x07]);
+const SYSTEM_ACCOUNT_SOLANA_UTILS: H160 =      H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08]);
+const SYSTEM_ACCOUNT_ERC20_FOR_SPL_FACTORY: H160 = H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x09]);
+const SYSTEM_ACCOUNT_WSOL: H160 =              H160([0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0a]);
 const SYSTEM_ACCOUNT_ECRECOVER: H160 =         H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]);
 const SYSTEM_ACCOUNT_SHA_256: H160 =           H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02]);
 const SYSTEM_ACCOUNT_RIPEMD160: H160 =         H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x03]);
@@ -34,75 +72,104 @@ const SYSTEM_ACCOUNT_BN256_ADD: H160 =         H160([0, 0, 0, 0, 0, 0, 0, 0, 0,
 const SYSTEM_ACCOUNT_BN256_SCALAR_MUL: H160 =  H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x07]);
 const SYSTEM_ACCOUNT_BN256_PAIRING: H160 =     H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08]);
 const SYSTEM_ACCOUNT_BLAKE2F: H160 =           H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x09]);
+const SYSTEM_ACCOUNT_P256VERIFY: H160 =        H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x00]);
 
+/// Gas cost of a Neon precompile call, Solana expenses are charged separately
+const NEON_PRECOMPILE_GAS: u64 = 1_000;
 
-/// Is precompile address
-#[must_use]
-pub fn is_precompile_address(address: &H160) -> bool {
-           *address == SYSTEM_ACCOUNT_QUERY
-        || *address == SYSTEM_ACCOUNT_NEON_TOKEN
-        || *address == SYSTEM_ACCOUNT_SPL_TOKEN
-        || *address == SYSTEM_ACCOUNT_METAPLEX
-        || *address == SYSTEM_ACCOUNT_ECRECOVER
-        || *address == SYSTEM_ACCOUNT_SHA_256
-        || *address == SYSTEM_ACCOUNT_RIPEMD160
-        || *address == SYSTEM_ACCOUNT_DATACOPY
-        || *address == SYSTEM_ACCOUNT_BIGMODEXP
-        || *address == SYSTEM_ACCOUNT_BN256_ADD
-        || *address == SYSTEM_ACCOUNT_BN256_SCALAR_MUL
-        || *address == SYSTEM_ACCOUNT_BN256_PAIRING
-        || *address == SYSTEM_ACCOUNT_BLAKE2F
-}
+/// ERC-20 interfaces of SPL mints, see `erc20_for_spl::ERC20_FOR_SPL_PREFIX`
+const SYSTEM_ACCOUNTS_ERC20_FOR_SPL: AddressRange = AddressRange(erc20_for_spl::is_erc20_for_spl_address);
 
-type PrecompileResult = Capture<(ExitReason, Vec<u8>), Infallible>;
 
-/// Call a precompile function
-#[must_use]
-pub fn call_precompile<B: AccountStorage>(
-    address: H160,
-    input: &[u8],
-    context: &evm::Context,
-    state: &mut ExecutorState<B>
-) -> Option<PrecompileResult> {
-    if address == SYSTEM_ACCOUNT_QUERY {
-        return Some(query_account::query_account(input, state));
-    }
-    if address == SYSTEM_ACCOUNT_NEON_TOKEN {
-        return Some(neon_token::neon_token(input, context, state));
-    }
-    if address == SYSTEM_ACCOUNT_SPL_TOKEN {
-        return Some(spl_token::spl_token(input, context, state));
-    }
-    if address == SYSTEM_ACCOUNT_METAPLEX {
-        return Some(metaplex::metaplex(input, context, state));
-    }
-    if address == SYSTEM_ACCOUNT_ECRECOVER {
-        return Some(ecrecover::ecrecover(input));
-    }
-    if address == SYSTEM_ACCOUNT_SHA_256 {
-        return Some(sha256::sha256(input));
-    }
-    if address == SYSTEM_ACCOUNT_RIPEMD160 {
-        return Some(ripemd160::ripemd160(input));
-    }
-    if address == SYSTEM_ACCOUNT_DATACOPY {
-        return Some(datacopy::datacopy(input));
-    }
-    if address == SYSTEM_ACCOUNT_BIGMODEXP {
-        return Some(big_mod_exp::big_mod_exp(input));
-    }
-    if address == SYSTEM_ACCOUNT_BN256_ADD {
-        return Some(bn256::bn256_add(input));
-    }
-    if address == SYSTEM_ACCOUNT_BN256_SCALAR_MUL {
-        return Some(bn256::bn256_scalar_mul(input));
-    }
-    if address == SYSTEM_ACCOUNT_BN256_PAIRING {
-        return Some(bn256::bn256_pairing(input));
+/// Precompile is bound to a single address or to a range of addresses
+trait PrecompileAddress {
+    fn matches(&self, address: &H160) -> bool;
+}
+
+impl PrecompileAddress for H160 {
+    fn matches(&self, address: &H160) -> bool {
+        self == address
     }
-    if address == SYSTEM_ACCOUNT_BLAKE2F {
-        return Some(blake2_f::blake2_f(input));
+}
+
+struct AddressRange(fn(&H160) -> bool);
+
+impl PrecompileAddress for AddressRange {
+    fn matches(&self, address: &H160) -> bool {
+        (self.0)(address)
     }
+}
+
+
+type PrecompileResult = Capture<(ExitReason, Vec<u8>), Infallible>;
+
+/// Declares the precompile registry.
+/// Generates `is_precompile_address`, `precompile_gas` and `call_precompile` from a single list of
+/// `ADDRESS [if enabled] => handler, gas: cost` entries.
+macro_rules! precompiles {
+    (
+        |$input:ident, $context:ident, $state:ident|
+        $( $address:ident $(if $enabled:expr)? => $handler:expr, gas: $gas:expr, )*
+    ) => {
+        /// Is precompile address
+        #[must_use]
+        pub fn is_precompile_address(address: &H160) -> bool {
+            $( (true $(&& $enabled)? && $address.matches(address)) )||*
+        }
+
+        /// Gas cost of a precompile call, `None` if the address is not a precompile
+        #[must_use]
+        pub fn precompile_gas(address: &H160, $input: &[u8]) -> Option<u64> {
+            $(
+                if true $(&& $enabled)? && $address.matches(address) {
+                    return Some($gas);
+                }
+            )*
+
+            None
+        }
+
+        /// Call a precompile function
+        #[must_use]
+        pub fn call_precompile<B: AccountStorage>(
+            address: H160,
+            $input: &[u8],
+            $context: &evm::Context,
+            $state: &mut ExecutorState<B>
+        ) -> Option<PrecompileResult> {
+            $(
+                if true $(&& $enabled)? && $address.matches(&address) {
+                    return Some($handler);
+                }
+            )*
+
+            None
+        }
+    };
+}
+
+precompiles! {
+    |input, context, state|
+
+    SYSTEM_ACCOUNT_QUERY if cfg!(feature = "query-account-precompile") => query_account::query_account(input, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_NEON_TOKEN if cfg!(feature = "neon-token-precompile") => neon_token::neon_token(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_SPL_TOKEN if cfg!(feature = "spl-token-precompile") => spl_token::spl_token(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_METAPLEX if cfg!(feature = "metaplex-precompile") => metaplex::metaplex(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_CALL_SOLANA if cfg!(feature = "call-solana-precompile") => call_solana::call_solana(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_ERC20_FOR_SPL_FACTORY if cfg!(feature = "erc20-for-spl-precompile") => erc20_for_spl::factory(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNTS_ERC20_FOR_SPL if cfg!(feature = "erc20-for-spl-precompile") => erc20_for_spl::erc20_for_spl(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_WSOL if cfg!(feature = "wsol-precompile") => wsol::wsol(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_SOLANA_UTILS if cfg!(feature = "solana-utils-precompile") => solana_utils::solana_utils(input, context, state), gas: NEON_PRECOMPILE_GAS,
+    SYSTEM_ACCOUNT_ED25519_VERIFY if cfg!(feature = "ed25519-verify-precompile") => ed25519_verify::ed25519_verify(input, state), gas: NEON_PRECOMPILE_GAS,
 
-    None
+    SYSTEM_ACCOUNT_ECRECOVER => ecrecover::ecrecover(input), gas: ecrecover::ECRECOVER_GAS,
+    SYSTEM_ACCOUNT_SHA_256 => sha256::sha256(input), gas: sha256::sha256_gas(input),
+    SYSTEM_ACCOUNT_RIPEMD160 => ripemd160::ripemd160(input), gas: ripemd160::ripemd160_gas(input),
+    SYSTEM_ACCOUNT_DATACOPY => datacopy::datacopy(input), gas: datacopy::datacopy_gas(input),
+    SYSTEM_ACCOUNT_BIGMODEXP => big_mod_exp::big_mod_exp(input), gas: big_mod_exp::big_mod_exp_gas(input),
+    SYSTEM_ACCOUNT_BN256_ADD => bn256::bn256_add(input), gas: bn256::BN256_ADD_GAS,
+    SYSTEM_ACCOUNT_BN256_SCALAR_MUL => bn256::bn256_scalar_mul(input), gas: bn256::BN256_SCALAR_MUL_GAS,
+    SYSTEM_ACCOUNT_BN256_PAIRING => bn256::bn256_pairing(input), gas: bn256::bn256_pairing_gas(input),
+    SYSTEM_ACCOUNT_BLAKE2F => blake2_f::blake2_f(input), gas: blake2_f::blake2_f_gas(input),
+    SYSTEM_ACCOUNT_P256VERIFY => p256_verify::p256_verify(input), gas: p256_verify::P256_VERIFY_GAS,
 }
@@ -1,7 +1,6 @@
-use std::convert::{Infallible, TryInto};
+use std::convert::Infallible;
 
-use arrayref::array_ref;
-use evm::{Capture, ExitReason, U256, H160};
+use evm::{Capture, ExitReason, U256, H160, H256};
 use solana_program::{pubkey::Pubkey, program_pack::Pack, program_error::ProgramError};
 use spl_associated_token_account::get_associated_token_address;
 
@@ -10,12 +9,22 @@ use crate::{
     executor::{ExecutorState}
 };
 
+use super::abi;
+
 
 // Neon token method ids:
 //--------------------------------------------------
 // withdraw(bytes32)           => 8e19899e
+// withdrawTo(bytes32)         => 9aadae4d
 //--------------------------------------------------
-const NEON_TOKEN_METHOD_WITHDRAW_ID: &[u8; 4]       = &[0x8e, 0x19, 0x89, 0x9e];
+const NEON_TOKEN_METHOD_WITHDRAW_ID: [u8; 4]       = [0x8e, 0x19, 0x89, 0x9e];
+const NEON_TOKEN_METHOD_WITHDRAW_TO_ID: [u8; 4]    = [0x9a, 0xad, 0xae, 0x4d];
+
+/// Withdraw(address indexed from, bytes32 indexed to, uint256 amount)
+const WITHDRAW_EVENT_TOPIC: H256 = H256([
+    0xc3, 0xde, 0xd2, 0xbe, 0x7d, 0xb2, 0x1b, 0x1a, 0xf9, 0x63, 0xf2, 0x67, 0xd2, 0x77, 0x50, 0xe4,
+    0xa1, 0x5f, 0x17, 0x45, 0x47, 0xd8, 0x8b, 0x88, 0x6b, 0x9b, 0xda, 0x43, 0xe2, 0xc6, 0xfa, 0x32,
+]);
 
 
 #[must_use]
@@ -30,84 +39,122 @@ pub fn neon_token<B: AccountStorage>(
 
     if context.apparent_value.is_zero() {
         // staticcall or call without value is not allowed
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("neon_token: call without value is not allowed")
     }
 
     if context.address != super::SYSTEM_ACCOUNT_NEON_TOKEN {
         // callcode or delegatecall is not allowed
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("neon_token: callcode or delegatecall is not allowed")
     }
 
-    let (method_id, rest) = input.split_at(4);
-    let method_id: &[u8; 4] = method_id.try_into().unwrap_or(&[0_u8; 4]);
+    let (method_id, arguments) = match abi::split_selector(input) {
+        Ok(split) => split,
+        Err(_) => return abi::revert("neon_token: method id is missing"),
+    };
 
-    if method_id == NEON_TOKEN_METHOD_WITHDRAW_ID  {
+    if (method_id == NEON_TOKEN_METHOD_WITHDRAW_ID) || (method_id == NEON_TOKEN_METHOD_WITHDRAW_TO_ID) {
         if state.is_static_context() {
-            let revert_message = b"neon_token: withdraw is not allowed in static context".to_vec();
-            return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
+            return abi::revert("neon_token: withdraw is not allowed in static context")
         }
 
         let source = context.caller; // caller contract
 
-        // owner of the associated token account
-        let destination = array_ref![rest, 0, 32];
-        let destination = Pubkey::new_from_array(*destination);
-
-
-        if withdraw(state, source, destination, context.apparent_value).is_err() {
-            let revert_message = b"neon_token: failed to withdraw NEON".to_vec();
-            return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
+        let destination = match arguments.pubkey(0) {
+            Ok(destination) => destination,
+            Err(_) => return abi::revert("neon_token: invalid destination"),
+        };
+
+        let result = if method_id == NEON_TOKEN_METHOD_WITHDRAW_ID {
+            // owner of the associated token account
+            associated_token_account(state, destination)
+                .and_then(|target_token| withdraw(context, state, source, target_token, context.apparent_value))
+        } else {
+            // existing token account of NEON mint
+            check_token_account(state, destination)
+                .and_then(|_| withdraw(context, state, source, destination, context.apparent_value))
+        };
+
+        if let Err(error) = result {
+            return abi::revert(&format!("neon_token: failed to withdraw NEON. {}", error))
         }
 
-        let mut output = vec![0_u8; 32];
-        output[31] = 1; // return true
-
+        let output = abi::encode(&[abi::Token::Bool(true)]);
         return Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), output));
     };
 
     debug_print!("neon_token UNKNOWN");
-    Capture::Exit((ExitReason::Fatal(evm::ExitFatal::NotSupported), vec![]))
+    abi::revert("neon_token: unknown method")
 }
 
 
+/// Returns the associated token account of the owner, the account creation is queued if it doesn't exist
+fn associated_token_account<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    owner: Pubkey,
+) -> abi::Result<Pubkey> {
+    let target_token = get_associated_token_address(&owner, state.backend.neon_token_mint());
+    let account = state.external_account(target_token)?;
+    if !spl_token::check_id(&account.owner) {
+        use spl_associated_token_account::instruction::create_associated_token_account;
+
+        let create_associated = create_associated_token_account(
+            state.backend.operator(),
+            &owner,
+            state.backend.neon_token_mint(),
+            &spl_token::ID
+        );
+        state.queue_external_instruction(create_associated, vec![], spl_token::state::Account::LEN);
+    }
+
+    Ok(target_token)
+}
+
+/// Checks that the account is an initialized and not frozen token account of NEON mint
+fn check_token_account<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    token_account: Pubkey,
+) -> abi::Result<()> {
+    let account = state.external_account(token_account)?;
+    if !spl_token::check_id(&account.owner) {
+        return Err!(ProgramError::IncorrectProgramId; "Account {} - is not SPL Token account", token_account);
+    }
+
+    let token = spl_token::state::Account::unpack(&account.data)?;
+    if token.mint != *state.backend.neon_token_mint() {
+        return Err!(ProgramError::InvalidArgument; "Account {} - invalid mint, expected {}", token_account, state.backend.neon_token_mint());
+    }
+
+    if token.is_frozen() {
+        return Err!(ProgramError::InvalidArgument; "Account {} - is frozen", token_account);
+    }
+
+    Ok(())
+}
+
 fn withdraw<B: AccountStorage>(
+    context: &evm::Context,
     state: &mut ExecutorState<B>,
     source: H160,
-    target: Pubkey,
+    target_token: Pubkey,
     value: U256
-) -> Result<(), ProgramError> {
+) -> abi::Result<()> {
     if value.is_zero() {
         return Ok(())
     }
 
     if state.balance(&source) < value {
-        return Err(ProgramError::InsufficientFunds);
+        return Err(ProgramError::InsufficientFunds.into());
     }
 
     let min_amount: u64 = u64::pow(10, u32::from(crate::config::token_mint::decimals()));
     let (spl_amount, remainder) = value.div_mod(U256::from(min_amount));
 
     if spl_amount > U256::from(u64::MAX) {
-        return Err(ProgramError::InvalidArgument);
+        return Err(ProgramError::InvalidArgument.into());
     }
 
     if !remainder.is_zero() {
-        return Err(ProgramError::InvalidArgument);
-    }
-
-
-    let target_token = get_associated_token_address(&target, state.backend.neon_token_mint());
-    let account = state.external_account(target_token)?;
-    if !spl_token::check_id(&account.owner) {
-        use spl_associated_token_account::instruction::create_associated_token_account;
-
-        let create_associated = create_associated_token_account(
-            state.backend.operator(),
-            &target,
-            state.backend.neon_token_mint(),
-            &spl_token::ID
-        );
-        state.queue_external_instruction(create_associated, vec![], spl_token::state::Account::LEN);
+        return Err(ProgramError::InvalidArgument.into());
     }
 
 
@@ -123,13 +170,17 @@ fn withdraw<B: AccountStorage>(
         &[],
         spl_amount.as_u64(),
         crate::config::token_mint::decimals()
-    ).unwrap();
+    )?;
     let transfer_seeds = vec![ b"Deposit".to_vec(), vec![bump_seed] ];
     state.queue_external_instruction(transfer, transfer_seeds, 0);
 
 
     state.withdraw(source, value);
 
+    let topics = vec![WITHDRAW_EVENT_TOPIC, H256::from(source), H256::from(target_token.to_bytes())];
+    let data = abi::encode(&[abi::Token::Uint(value)]);
+    state.log(context.address, topics, data);
+
 
     Ok(())
 
@@ -0,0 +1,106 @@
+use std::convert::Infallible;
+
+use arrayref::array_refs;
+use evm::{Capture, ExitReason, U256};
+use ecdsa::hazmat::VerifyPrimitive;
+use p256::elliptic_curve::{ops::Reduce, sec1::FromEncodedPoint};
+use p256::{ecdsa::Signature, AffinePoint, EncodedPoint, Scalar};
+
+/// Gas cost of `p256_verify` (RIP-7212)
+pub const P256_VERIFY_GAS: u64 = 3_450;
+
+/// RIP-7212: verifies secp256r1 (P-256) signature.
+/// Input: `hash (32) || r (32) || s (32) || x (32) || y (32)`.
+/// Returns 1 as 32 bytes word if signature is valid, empty output otherwise.
+#[must_use]
+pub fn p256_verify(
+    input: &[u8]
+) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+    debug_print!("p256_verify");
+    debug_print!("input: {}", &hex::encode(input));
+
+    if input.len() != 160 {
+        return Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), vec![]));
+    }
+
+    let input = arrayref::array_ref![input, 0, 160];
+    let (hash, r, s, x, y) = array_refs![input, 32, 32, 32, 32, 32];
+
+    if !verify(hash, r, s, x, y) {
+        return Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), vec![]));
+    }
+
+    let mut result = vec![0_u8; 32];
+    U256::one().to_big_endian(&mut result);
+
+    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), result))
+}
+
+fn verify(hash: &[u8; 32], r: &[u8; 32], s: &[u8; 32], x: &[u8; 32], y: &[u8; 32]) -> bool {
+    let signature = match Signature::from_scalars(*r, *s) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+
+    let point = EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+    let key = match Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&point)) {
+        Some(key) => key,
+        None => return false,
+    };
+
+    let hash = <Scalar as Reduce<p256::U256>>::from_be_bytes_reduced((*hash).into());
+    key.verify_prehashed(hash, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // hash || r || s || x || y, signed with a P-256 key over sha256("neon p256")
+    const VECTOR: &str = "44df3876e450091bc3686e01273ba592fa574b81095ee89d4a87e856f10dd6eb\
+        35d58c8b93f94170fcf84120b4e16e2db6891b37cbdb52b251d0de8a50a88639\
+        fe8591250de5e2e1eb901fa24fa5cbc8901793af67fe814b1a7b155bb7f5756a\
+        9fad84aeae08bbef7f010014d82cef6a09de2b0cf871b5ce0c4f1d13a59a5934\
+        07cb45769f1070e2c2470fe5b1bfe63133c0b0cdc64ea4bf3791a8ec2a07fd4f";
+
+    fn output(input: &[u8]) -> Vec<u8> {
+        match p256_verify(input) {
+            Capture::Exit((ExitReason::Succeed(_), output)) => output,
+            _ => panic!("p256_verify must succeed"),
+        }
+    }
+
+    #[test]
+    fn valid_signature() {
+        let input = hex::decode(VECTOR).unwrap();
+
+        let mut expected = vec![0_u8; 32];
+        expected[31] = 1;
+        assert_eq!(output(&input), expected);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let mut input = hex::decode(VECTOR).unwrap();
+        input[0] ^= 1;
+
+        assert!(output(&input).is_empty());
+    }
+
+    #[test]
+    fn point_not_on_curve() {
+        let mut input = hex::decode(VECTOR).unwrap();
+        input[159] ^= 1;
+
+        assert!(output(&input).is_empty());
+    }
+
+    #[test]
+    fn invalid_input_length() {
+        let input = hex::decode(VECTOR).unwrap();
+
+        assert!(output(&input[..159]).is_empty());
+        assert!(output(&[input.as_slice(), &[0]].concat()).is_empty());
+        assert!(output(&[]).is_empty());
+    }
+}
@@ -1,11 +1,13 @@
-use std::convert::{Infallible, TryInto};
+use std::convert::Infallible;
 
-use arrayref::{array_ref, array_refs};
-use evm::{Capture, ExitReason, U256};
-use solana_program::{pubkey::Pubkey, program_error::ProgramError};
+use evm::{Capture, ExitReason};
+use solana_program::{pubkey::Pubkey, program_error::ProgramError, program_option::COption, program_pack::Pack};
+use spl_token_2022::state::{Account, Mint};
 
 use crate::{account_storage::AccountStorage, executor::ExecutorState};
 
+use super::abi::{self, Decoder, Token};
+
 
 // QueryAccount method ids:
 //-------------------------------------------
@@ -16,20 +18,28 @@ use crate::{account_storage::AccountStorage, executor::ExecutorState};
 // executable(uint256)          => 0xc219a785
 // rent_epoch(uint256)          => 0xc4d369b5
 // data(uint256,uint64,uint64)  => 0x43ca5161
+// batch((uint256,uint64,uint64)[]) => 0x00561ba5
+// tokenAccount(uint256)        => 0x50dfc5b1
+// tokenMint(uint256)           => 0x306c9697
 //-------------------------------------------
 
-const QUERY_ACCOUNT_METHOD_CACHE_ID: &[u8; 4] = &[0x2b, 0x3c, 0x83, 0x22];
-const QUERY_ACCOUNT_METHOD_OWNER_ID: &[u8; 4] = &[0xa1, 0x23, 0xc3, 0x3e];
-const QUERY_ACCOUNT_METHOD_LENGTH_ID: &[u8; 4] = &[0xaa, 0x8b, 0x99, 0xd2];
-const QUERY_ACCOUNT_METHOD_LAMPORTS_ID: &[u8; 4] = &[0x74, 0x8f, 0x2d, 0x8a];
-const QUERY_ACCOUNT_METHOD_EXECUTABLE_ID: &[u8; 4] = &[0xc2, 0x19, 0xa7, 0x85];
-const QUERY_ACCOUNT_METHOD_RENT_EPOCH_ID: &[u8; 4] = &[0xc4, 0xd3, 0x69, 0xb5];
-const QUERY_ACCOUNT_METHOD_DATA_ID: &[u8; 4] = &[0x43, 0xca, 0x51, 0x61];
+const QUERY_ACCOUNT_METHOD_CACHE_ID: [u8; 4] = [0x2b, 0x3c, 0x83, 0x22];
+const QUERY_ACCOUNT_METHOD_OWNER_ID: [u8; 4] = [0xa1, 0x23, 0xc3, 0x3e];
+const QUERY_ACCOUNT_METHOD_LENGTH_ID: [u8; 4] = [0xaa, 0x8b, 0x99, 0xd2];
+const QUERY_ACCOUNT_METHOD_LAMPORTS_ID: [u8; 4] = [0x74, 0x8f, 0x2d, 0x8a];
+const QUERY_ACCOUNT_METHOD_EXECUTABLE_ID: [u8; 4] = [0xc2, 0x19, 0xa7, 0x85];
+const QUERY_ACCOUNT_METHOD_RENT_EPOCH_ID: [u8; 4] = [0xc4, 0xd3, 0x69, 0xb5];
+const QUERY_ACCOUNT_METHOD_DATA_ID: [u8; 4] = [0x43, 0xca, 0x51, 0x61];
+const QUERY_ACCOUNT_METHOD_BATCH_ID: [u8; 4] = [0x00, 0x56, 0x1b, 0xa5];
+const QUERY_ACCOUNT_METHOD_TOKEN_ACCOUNT_ID: [u8; 4] = [0x50, 0xdf, 0xc5, 0xb1];
+const QUERY_ACCOUNT_METHOD_TOKEN_MINT_ID: [u8; 4] = [0x30, 0x6c, 0x96, 0x97];
+
+/// Maximum total length of the data returned by a single `batch` call
+const QUERY_ACCOUNT_BATCH_MAX_DATA_LEN: usize = 8 * 1024;
 
 
 
 #[must_use]
-#[allow(clippy::too_many_lines)]
 pub fn query_account<B: AccountStorage>(
     input: &[u8],
     state: &mut ExecutorState<B>
@@ -38,127 +48,105 @@ pub fn query_account<B: AccountStorage>(
 {
     debug_print!("query_account({})", hex::encode(input));
 
-    let (method_id, rest) = input.split_at(4);
-    let method_id: &[u8; 4] = method_id.try_into().unwrap_or(&[0_u8; 4]);
+    let (method_id, arguments) = match abi::split_selector(input) {
+        Ok(split) => split,
+        Err(err) => return abi::revert(&format!("QueryAccount failed: {}", err)),
+    };
+
+    if method_id == QUERY_ACCOUNT_METHOD_BATCH_ID {
+        debug_print!("query_account.batch");
 
-    let (account_address, rest) = rest.split_at(32);
-    let account_address = Pubkey::new(account_address);
+        return exit("batch", batch(state, arguments));
+    }
+
+    let account_address = match arguments.pubkey(0) {
+        Ok(account_address) => account_address,
+        Err(err) => return abi::revert(&format!("QueryAccount failed: {}", err)),
+    };
 
     match method_id {
         QUERY_ACCOUNT_METHOD_CACHE_ID => {
-            let arguments = array_ref![rest, 0, 64];
-            let (offset, length) = array_refs!(arguments, 32, 32);
-            let offset = U256::from_big_endian_fast(offset).as_usize();
-            let length = U256::from_big_endian_fast(length).as_usize();
-
-            debug_print!("query_account.cache({}, {}, {})", account_address, offset, length);
-
-            match cache_account(state, account_address, offset, length) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.cache failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            let result = read_range(arguments)
+                .and_then(|(offset, length)| {
+                    debug_print!("query_account.cache({}, {}, {})", account_address, offset, length);
+                    cache_account(state, account_address, offset, length)
+                });
+
+            exit("cache", result)
         },
         QUERY_ACCOUNT_METHOD_OWNER_ID => {
             debug_print!("query_account.owner({})", account_address);
 
-            match account_owner(state, account_address) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.owner failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            exit("owner", account_owner(state, account_address))
         },
         QUERY_ACCOUNT_METHOD_LENGTH_ID => {
             debug_print!("query_account.length({})", account_address);
 
-            match account_data_length(state, account_address) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.length failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            exit("length", account_data_length(state, account_address))
         },
         QUERY_ACCOUNT_METHOD_LAMPORTS_ID => {
             debug_print!("query_account.lamports({})", account_address);
 
-            match account_lamports(state, account_address) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.lamports failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            exit("lamports", account_lamports(state, account_address))
         },
         QUERY_ACCOUNT_METHOD_EXECUTABLE_ID => {
             debug_print!("query_account.executable({})", account_address);
 
-            match account_is_executable(state, account_address) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.executable failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            exit("executable", account_is_executable(state, account_address))
         },
         QUERY_ACCOUNT_METHOD_RENT_EPOCH_ID => {
             debug_print!("query_account.rent_epoch({})", account_address);
 
-            match account_rent_epoch(state, account_address) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.rent_epoch failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            exit("rent_epoch", account_rent_epoch(state, account_address))
         },
         QUERY_ACCOUNT_METHOD_DATA_ID => {
-            let arguments = array_ref![rest, 0, 64];
-            let (offset, length) = array_refs!(arguments, 32, 32);
-            let offset = U256::from_big_endian_fast(offset).as_usize();
-            let length = U256::from_big_endian_fast(length).as_usize();
-            debug_print!("query_account.data({}, {}, {})", account_address, offset, length);
-
-            match account_data(state, account_address, offset, length) {
-                Ok(value) => {
-                    Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
-                }
-                Err(err) => {
-                    let revert_message = format!("QueryAccount.data failed: {}", err).as_bytes().to_vec();
-                    Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), revert_message))
-                }
-            }
+            let result = read_range(arguments)
+                .and_then(|(offset, length)| {
+                    debug_print!("query_account.data({}, {}, {})", account_address, offset, length);
+                    account_data(state, account_address, offset, length)
+                });
+
+            exit("data", result)
+        },
+        QUERY_ACCOUNT_METHOD_TOKEN_ACCOUNT_ID => {
+            debug_print!("query_account.tokenAccount({})", account_address);
+
+            exit("tokenAccount", token_account(state, account_address))
+        },
+        QUERY_ACCOUNT_METHOD_TOKEN_MINT_ID => {
+            debug_print!("query_account.tokenMint({})", account_address);
+
+            exit("tokenMint", token_mint(state, account_address))
         },
         _ => {
             debug_print!("query_account UNKNOWN {:?}", method_id);
-            Capture::Exit((ExitReason::Fatal(evm::ExitFatal::NotSupported), vec![]))
+            abi::revert("QueryAccount: unknown method")
         }
     }
 }
 
+fn exit(method: &str, result: abi::Result<Vec<u8>>) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+    result.map_or_else(
+        |err| abi::revert(&format!("QueryAccount.{} failed: {}", method, err)),
+        |value| Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), value))
+    )
+}
+
+/// Reads (offset, length) arguments which follow the account address
+fn read_range(arguments: Decoder<'_>) -> abi::Result<(usize, usize)> {
+    let offset = arguments.usize(1)?;
+    let length = arguments.usize(2)?;
+
+    Ok((offset, length))
+}
+
 
 fn cache_account<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
     offset: usize,
     length: usize
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     state.external_account_partial_cache(account_address, offset, length)?;
 
     Ok(Vec::new())
@@ -167,63 +155,47 @@ fn cache_account<B: AccountStorage>(
 fn account_owner<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
-    Ok(account.owner.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(account.owner.to_bytes())]))
 }
 
 fn account_lamports<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
-    let lamports: U256 = account.lamports.into(); // pad to 32 bytes
-    let mut bytes = vec![0_u8; 32];
-    lamports.into_big_endian_fast(&mut bytes);
-
-    Ok(bytes)
+    Ok(abi::encode(&[Token::uint(account.lamports)]))
 }
 
 fn account_rent_epoch<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
-    let epoch: U256 = account.rent_epoch.into(); // pad to 32 bytes
-    let mut bytes = vec![0_u8; 32];
-    epoch.into_big_endian_fast(&mut bytes);
-
-    Ok(bytes)
+    Ok(abi::encode(&[Token::uint(account.rent_epoch)]))
 }
 
 fn account_is_executable<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
-    let is_executable: U256 = if account.executable { U256::one() } else { U256::zero() };
-    let mut bytes = vec![0_u8; 32];
-    is_executable.into_big_endian_fast(&mut bytes);
-
-    Ok(bytes)
+    Ok(abi::encode(&[Token::Bool(account.executable)]))
 }
 
 
 fn account_data_length<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account_address: Pubkey,
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
-    let length: U256 = account.data_total_len.into(); // pad to 32 bytes
-    let mut bytes = vec![0_u8; 32];
-    length.into_big_endian_fast(&mut bytes);
-
-    Ok(bytes)
+    Ok(abi::encode(&[Token::uint(account.data_total_len)]))
 }
 
 fn account_data<B: AccountStorage>(
@@ -231,19 +203,19 @@ fn account_data<B: AccountStorage>(
     account_address: Pubkey,
     offset: usize,
     length: usize
-) -> Result<Vec<u8>, ProgramError> {
+) -> abi::Result<Vec<u8>> {
     let account = state.external_account_partial(account_address)?;
 
     if length == 0 {
-        return Err(ProgramError::InvalidArgument);
+        return Err(ProgramError::InvalidArgument.into());
     }
 
     if offset < account.data_offset {
-        return Err(ProgramError::InvalidArgument);
+        return Err(ProgramError::InvalidArgument.into());
     }
 
     if offset.saturating_add(length) > account.data_offset.saturating_add(account.data.len()) {
-        return Err(ProgramError::InvalidArgument);
+        return Err(ProgramError::InvalidArgument.into());
     }
 
     debug_print!("query_account.data got {} bytes", length);
@@ -253,4 +225,94 @@ fn account_data<B: AccountStorage>(
     let data = &account.data[begin..end];
 
     Ok(data.to_vec())
-}
\ No newline at end of file
+}
+
+/// Reads owner, lamports, executable flag, data length and a data slice
+/// for every `(account, offset, length)` request.
+/// Several slices of the same account are requested by repeating the account.
+fn batch<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    arguments: Decoder<'_>,
+) -> abi::Result<Vec<u8>> {
+    let requests = read_batch_requests(arguments)?;
+
+    let total_len = requests.iter().fold(0_usize, |total, (_, _, length)| total.saturating_add(*length));
+    if total_len > QUERY_ACCOUNT_BATCH_MAX_DATA_LEN {
+        return Err!(ProgramError::InvalidArgument; "Account batch: data len {} exceeds {}", total_len, QUERY_ACCOUNT_BATCH_MAX_DATA_LEN);
+    }
+
+    // Result - array of (uint256 owner, uint256 lamports, bool executable, uint256 length, bytes data)
+    let mut results = Vec::with_capacity(requests.len());
+
+    for (account_address, offset, length) in requests {
+        let account = state.external_account_slice(account_address, offset, length)?;
+
+        results.push(Token::Tuple(vec![
+            Token::FixedBytes(account.owner.to_bytes()),
+            Token::uint(account.lamports),
+            Token::Bool(account.executable),
+            Token::uint(account.data_total_len),
+            Token::Bytes(account.data),
+        ]));
+    }
+
+    Ok(abi::encode(&[Token::Array(results)]))
+}
+
+fn read_batch_requests(arguments: Decoder<'_>) -> abi::Result<Vec<(Pubkey, usize, usize)>> {
+    let (count, elements) = arguments.array(0)?;
+
+    (0..count)
+        .map(|i| {
+            let request = elements.static_tuple(i * 3, 3)?;
+            Ok((request.pubkey(0)?, request.usize(1)?, request.usize(2)?))
+        })
+        .collect()
+}
+
+fn coption_to_token(value: COption<Pubkey>) -> Token {
+    Token::FixedBytes(value.map_or([0_u8; 32], Pubkey::to_bytes))
+}
+
+/// Decodes the base state of SPL Token or Token-2022 account into
+/// (mint, owner, amount, delegate, delegated amount, state, is native, close authority)
+fn token_account<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    account_address: Pubkey,
+) -> abi::Result<Vec<u8>> {
+    let account = state.external_account_slice(account_address, 0, Account::LEN)?;
+    spl_token_2022::check_spl_token_program_account(&account.owner)?;
+
+    let token = Account::unpack(&account.data)?;
+
+    Ok(abi::encode(&[
+        Token::FixedBytes(token.mint.to_bytes()),
+        Token::FixedBytes(token.owner.to_bytes()),
+        Token::uint(token.amount),
+        coption_to_token(token.delegate),
+        Token::uint(token.delegated_amount),
+        Token::uint(token.state as u8),
+        Token::Bool(token.is_native.is_some()),
+        coption_to_token(token.close_authority),
+    ]))
+}
+
+/// Decodes the base state of SPL Token or Token-2022 mint into
+/// (mint authority, supply, decimals, is initialized, freeze authority)
+fn token_mint<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    account_address: Pubkey,
+) -> abi::Result<Vec<u8>> {
+    let account = state.external_account_slice(account_address, 0, Mint::LEN)?;
+    spl_token_2022::check_spl_token_program_account(&account.owner)?;
+
+    let mint = Mint::unpack(&account.data)?;
+
+    Ok(abi::encode(&[
+        coption_to_token(mint.mint_authority),
+        Token::uint(mint.supply),
+        Token::uint(mint.decimals),
+        Token::Bool(mint.is_initialized),
+        coption_to_token(mint.freeze_authority),
+    ]))
+}
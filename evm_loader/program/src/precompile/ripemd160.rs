@@ -23,4 +23,10 @@ pub fn ripemd160(
     debug_print!("{}", &hex::encode(&result));
 
     Capture::Exit((ExitReason::Succeed(evm::ExitSucceed::Returned), result))
+}
+
+/// Gas cost: 600 plus 120 per word of input
+#[must_use]
+pub fn ripemd160_gas(input: &[u8]) -> u64 {
+    600 + 120 * ((input.len() as u64 + 31) / 32)
 }
\ No newline at end of file
@@ -17,4 +17,10 @@ pub fn sha256(
         ExitReason::Succeed(evm::ExitSucceed::Returned),
         hash.to_bytes().to_vec(),
     ))
+}
+
+/// Gas cost: 60 plus 12 per word of input
+#[must_use]
+pub fn sha256_gas(input: &[u8]) -> u64 {
+    60 + 12 * ((input.len() as u64 + 31) / 32)
 }
\ No newline at end of file
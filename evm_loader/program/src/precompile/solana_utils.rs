@@ -0,0 +1,150 @@
+use std::convert::Infallible;
+
+use evm::{Capture, ExitReason};
+use solana_program::{
+    epoch_schedule::EpochSchedule,
+    program_error::ProgramError,
+    pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN},
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+use crate::{account_storage::AccountStorage, executor::ExecutorState};
+
+use super::abi::{self, Decoder, Token};
+
+// [0x4a, 0xf7, 0xf2, 0x48] : "createProgramAddress(bytes[],bytes32)"
+// [0x26, 0x6e, 0xe4, 0x2f] : "findProgramAddress(bytes[],bytes32)"
+// [0xc8, 0x4f, 0x98, 0x2b] : "createWithSeed(bytes32,string,bytes32)"
+// [0xee, 0xd9, 0x47, 0xb1] : "getClock()"
+// [0xd0, 0x10, 0xcf, 0x62] : "minimumBalance(uint64)"
+// [0x3d, 0x32, 0xf2, 0x39] : "getEpochSchedule()"
+
+#[must_use]
+pub fn solana_utils<B: AccountStorage>(
+    input: &[u8],
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    if !context.apparent_value.is_zero() {
+        return abi::revert("SolanaUtils: value transfer is not allowed")
+    }
+
+    let result = abi::split_selector(input).and_then(|(selector, input)| match selector {
+        [0x4a, 0xf7, 0xf2, 0x48] => { // "createProgramAddress(bytes[],bytes32)"
+            let seeds = read_seeds(input, 0)?;
+            let program_id = input.pubkey(1)?;
+            create_program_address(&seeds, &program_id)
+        }
+        [0x26, 0x6e, 0xe4, 0x2f] => { // "findProgramAddress(bytes[],bytes32)"
+            let seeds = read_seeds(input, 0)?;
+            let program_id = input.pubkey(1)?;
+            find_program_address(&seeds, &program_id)
+        }
+        [0xc8, 0x4f, 0x98, 0x2b] => { // "createWithSeed(bytes32,string,bytes32)"
+            let base = input.pubkey(0)?;
+            let seed = input.string(1)?;
+            let owner = input.pubkey(2)?;
+            create_with_seed(&base, seed, &owner)
+        }
+        [0xee, 0xd9, 0x47, 0xb1] => { // "getClock()"
+            clock(state)
+        }
+        [0xd0, 0x10, 0xcf, 0x62] => { // "minimumBalance(uint64)"
+            input.usize(0)
+                .and_then(minimum_balance)
+        }
+        [0x3d, 0x32, 0xf2, 0x39] => { // "getEpochSchedule()"
+            epoch_schedule()
+        }
+        _ => {
+            Err!(ProgramError::InvalidInstructionData; "SolanaUtils: unknown method")
+        }
+    });
+
+    abi::exit(result)
+}
+
+
+fn create_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> abi::Result<Vec<u8>> {
+    let address = Pubkey::create_program_address(seeds, program_id)
+        .map_err(|e| E!(ProgramError::InvalidSeeds; "SolanaUtils: {}", e))?;
+
+    Ok(abi::encode(&[Token::FixedBytes(address.to_bytes())]))
+}
+
+fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> abi::Result<Vec<u8>> {
+    if seeds.len() >= MAX_SEEDS {
+        // One seed is reserved for the bump seed
+        return Err!(ProgramError::InvalidSeeds; "SolanaUtils: too many seeds {}", seeds.len());
+    }
+
+    let (address, bump_seed) = Pubkey::try_find_program_address(seeds, program_id)
+        .ok_or_else(|| E!(ProgramError::InvalidSeeds; "SolanaUtils: unable to find a viable bump seed"))?;
+
+    Ok(abi::encode(&[Token::FixedBytes(address.to_bytes()), Token::uint(bump_seed)]))
+}
+
+fn create_with_seed(base: &Pubkey, seed: &str, owner: &Pubkey) -> abi::Result<Vec<u8>> {
+    let address = Pubkey::create_with_seed(base, seed, owner)
+        .map_err(|e| E!(ProgramError::InvalidSeeds; "SolanaUtils: {}", e))?;
+
+    Ok(abi::encode(&[Token::FixedBytes(address.to_bytes())]))
+}
+
+/// Returns (uint64 slot, uint64 epoch, int64 unix_timestamp) of the block the transaction is executed in
+fn clock<B: AccountStorage>(state: &ExecutorState<B>) -> abi::Result<Vec<u8>> {
+    let slot = state.backend.block_number().as_u64();
+    let epoch = EpochSchedule::get()?.get_epoch(slot);
+    // Block timestamp is never negative, int64 and uint64 have the same encoding
+    let unix_timestamp = state.backend.block_timestamp().as_u64();
+
+    Ok(abi::encode(&[
+        Token::uint(slot),
+        Token::uint(epoch),
+        Token::uint(unix_timestamp),
+    ]))
+}
+
+fn minimum_balance(data_len: usize) -> abi::Result<Vec<u8>> {
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(data_len);
+
+    Ok(abi::encode(&[Token::uint(lamports)]))
+}
+
+/// Returns (uint64 slots_per_epoch, uint64 leader_schedule_slot_offset, bool warmup,
+///          uint64 first_normal_epoch, uint64 first_normal_slot)
+fn epoch_schedule() -> abi::Result<Vec<u8>> {
+    let schedule = EpochSchedule::get()?;
+
+    Ok(abi::encode(&[
+        Token::uint(schedule.slots_per_epoch),
+        Token::uint(schedule.leader_schedule_slot_offset),
+        Token::Bool(schedule.warmup),
+        Token::uint(schedule.first_normal_epoch),
+        Token::uint(schedule.first_normal_slot),
+    ]))
+}
+
+
+/// Reads `bytes[]` seeds
+fn read_seeds(input: Decoder<'_>, index: usize) -> abi::Result<Vec<&[u8]>> {
+    let (count, elements) = input.array(index)?;
+    if count > MAX_SEEDS {
+        return Err!(ProgramError::InvalidSeeds; "SolanaUtils: too many seeds {}", count);
+    }
+
+    let mut seeds = Vec::with_capacity(count);
+    for i in 0..count {
+        let seed = elements.bytes(i)?;
+        if seed.len() > MAX_SEED_LEN {
+            return Err!(ProgramError::InvalidSeeds; "SolanaUtils: seed length {} exceeds {}", seed.len(), MAX_SEED_LEN);
+        }
+
+        seeds.push(seed);
+    }
+
+    Ok(seeds)
+}
@@ -1,16 +1,30 @@
 use std::convert::{Infallible, TryInto};
 
-use evm::{Capture, ExitReason, U256, ExitSucceed, ExitRevert};
+use evm::{Capture, ExitReason};
 use solana_program::{
-    pubkey::Pubkey, rent::Rent, sysvar::Sysvar, 
+    pubkey::Pubkey, rent::Rent, epoch_schedule::EpochSchedule, sysvar::Sysvar, 
     program_error::ProgramError, system_instruction, program_pack::Pack
 };
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::{
+        ExtensionType, StateWithExtensions,
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+    },
+    instruction::AuthorityType,
+    state::{Account, Mint, Multisig},
+};
 
 use crate::{
     account_storage::AccountStorage,
     executor::{ExecutorState, OwnedAccountInfo}, account::ACCOUNT_SEED_VERSION,
 };
 
+use super::abi::{self, Decoder, Token};
+
 // [0xa9, 0xc1, 0x58, 0x06] : "approve(bytes32,bytes32,uint64)",
 // [0xe3, 0x41, 0x08, 0x55] : "burn(bytes32,uint64)",
 // [0x57, 0x82, 0xa0, 0x43] : "closeAccount(bytes32)",
@@ -27,6 +41,16 @@ use crate::{
 // [0xb7, 0x5c, 0x7d, 0xc6] : "revoke(bytes32)",
 // [0xc2, 0x59, 0xdd, 0xfe] : "thaw(bytes32)",
 // [0x78, 0x42, 0x3b, 0xcf] : "transfer(bytes32,bytes32,uint64)"
+// [0xc6, 0x21, 0x28, 0x35] : "transferChecked(bytes32,bytes32,uint64,uint8)"
+// [0x68, 0x0e, 0x7c, 0xd8] : "mintToChecked(bytes32,uint64,uint8)"
+// [0x5f, 0x96, 0xd4, 0xc0] : "burnChecked(bytes32,uint64,uint8)"
+// [0x6c, 0xf2, 0x40, 0x90] : "setAuthority(bytes32,uint8,bytes32)"
+// [0xd9, 0x96, 0xd3, 0x5c] : "syncNative(bytes32)"
+// [0xd6, 0xda, 0xd6, 0xea] : "initializeMultisig(bytes32,uint8,bytes32[])"
+// [0xe7, 0x77, 0x47, 0x2f] : "getAssociatedTokenAddress(bytes32,bytes32)"
+// [0x1f, 0x3b, 0x67, 0x38] : "createAssociatedTokenAccount(bytes32,bytes32)"
+//
+// Operations are signed by the contract, or by the multisig account where the contract is one of the signers
 
 #[must_use]
 pub fn spl_token<B: AccountStorage>(
@@ -36,141 +60,184 @@ pub fn spl_token<B: AccountStorage>(
 ) -> Capture<(ExitReason, Vec<u8>), Infallible>
 {
     if !context.apparent_value.is_zero() {
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("SPL Token: value transfer is not allowed")
     }
 
     if context.address == context.caller { 
         // callcode is not allowed
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("SPL Token: callcode is not allowed")
     }
 
     if (context.address != super::SYSTEM_ACCOUNT_SPL_TOKEN) && (state.call_depth() != 1) {
         // delegatecall is only allowed in top level contract
-        return Capture::Exit((ExitReason::Revert(evm::ExitRevert::Reverted), vec![]))
+        return abi::revert("SPL Token: delegatecall is only allowed in top level contract")
     }
 
 
-    let (selector, input) = input.split_at(4);
-
-    let result = match *selector {
+    let result = abi::split_selector(input).and_then(|(selector, input)| match selector {
         [0xb1, 0x1e, 0xcc, 0x50] => { // initializeMint(bytes32 seed, uint8 decimals)
-            let seed = read_salt(input);
-            let decimals = read_u8(&input[32..]);
+            let seed = input.bytes32(0)?;
+            let decimals = input.u8(1)?;
 
-            initialize_mint(context, state, seed, decimals, None, None)
+            initialize_mint(context, state, &seed, decimals, None, None)
         }
         [0xc3, 0xf3, 0xf2, 0xf2] => { // initializeMint(bytes32 seed, uint8 decimals, bytes32 mint_authority, bytes32 freeze_authority)
-            let seed = read_salt(input);
-            let decimals = read_u8(&input[32..]);
-            let mint_authority = read_pubkey(&input[64..]);
-            let freeze_authority = read_pubkey(&input[96..]);
-            initialize_mint(context, state, seed, decimals, Some(mint_authority), Some(freeze_authority))
+            let seed = input.bytes32(0)?;
+            let decimals = input.u8(1)?;
+            let mint_authority = input.pubkey(2)?;
+            let freeze_authority = input.pubkey(3)?;
+            initialize_mint(context, state, &seed, decimals, Some(mint_authority), Some(freeze_authority))
         }
         [0xda, 0xa1, 0x2c, 0x5c] => { // initializeAccount(bytes32 seed, bytes32 mint)
-            let seed = read_salt(input);
-            let mint = read_pubkey(&input[32..]);
+            let seed = input.bytes32(0)?;
+            let mint = input.pubkey(1)?;
 
-            initialize_account(context, state, seed, mint, None)
+            initialize_account(context, state, &seed, mint, None)
         }
         [0xfc, 0x86, 0xb7, 0x17] => { // initializeAccount(bytes32 seed, bytes32 mint, bytes32 owner)
-            let seed = read_salt(input);
-            let mint = read_pubkey(&input[32..]);
-            let owner = read_pubkey(&input[64..]);
-            initialize_account(context, state, seed, mint, Some(owner))
+            let seed = input.bytes32(0)?;
+            let mint = input.pubkey(1)?;
+            let owner = input.pubkey(2)?;
+            initialize_account(context, state, &seed, mint, Some(owner))
         }
         [0x57, 0x82, 0xa0, 0x43] => { // closeAccount(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             close_account(context, state, account)
         }
         [0xa9, 0xc1, 0x58, 0x06] => { // approve(bytes32 source, bytes32 target, uint64 amount)
-            let source = read_pubkey(input);
-            let target = read_pubkey(&input[32..]);
-            let amount = read_u64(&input[64..]);
+            let source = input.pubkey(0)?;
+            let target = input.pubkey(1)?;
+            let amount = input.u64(2)?;
             approve(context, state, source, target, amount)
         }
         [0xb7, 0x5c, 0x7d, 0xc6] => { // revoke(bytes32 source)
-            let source = read_pubkey(input);
+            let source = input.pubkey(0)?;
             revoke(context, state, source)
         }
         [0x78, 0x42, 0x3b, 0xcf] => { // transfer(bytes32 source, bytes32 target, uint64 amount)
-            let source = read_pubkey(input);
-            let target = read_pubkey(&input[32..]);
-            let amount = read_u64(&input[64..]);
+            let source = input.pubkey(0)?;
+            let target = input.pubkey(1)?;
+            let amount = input.u64(2)?;
             transfer(context, state, source, target, amount)
         }
+        [0xc6, 0x21, 0x28, 0x35] => { // transferChecked(bytes32 source, bytes32 target, uint64 amount, uint8 decimals)
+            let source = input.pubkey(0)?;
+            let target = input.pubkey(1)?;
+            let amount = input.u64(2)?;
+            let decimals = input.u8(3)?;
+            transfer_checked(context, state, source, target, amount, decimals)
+        }
         [0xa9, 0x05, 0x74, 0x01] => { // mintTo(bytes32 account, uint64 amount)
-            let account = read_pubkey(input);
-            let amount = read_u64(&input[32..]);
-            mint(context, state, account, amount)
+            let account = input.pubkey(0)?;
+            let amount = input.u64(1)?;
+            mint(context, state, account, amount, None)
+        }
+        [0x68, 0x0e, 0x7c, 0xd8] => { // mintToChecked(bytes32 account, uint64 amount, uint8 decimals)
+            let account = input.pubkey(0)?;
+            let amount = input.u64(1)?;
+            let decimals = input.u8(2)?;
+            mint(context, state, account, amount, Some(decimals))
         }
         [0xe3, 0x41, 0x08, 0x55] => { // burn(bytes32 account, uint64 amount)
-            let account = read_pubkey(input);
-            let amount = read_u64(&input[32..]);
-            burn(context, state, account, amount)
+            let account = input.pubkey(0)?;
+            let amount = input.u64(1)?;
+            burn(context, state, account, amount, None)
+        }
+        [0x5f, 0x96, 0xd4, 0xc0] => { // burnChecked(bytes32 account, uint64 amount, uint8 decimals)
+            let account = input.pubkey(0)?;
+            let amount = input.u64(1)?;
+            let decimals = input.u8(2)?;
+            burn(context, state, account, amount, Some(decimals))
         }
         [0xec, 0x13, 0xcc, 0x7b] => { // freeze(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             freeze(context, state, account)
         }
         [0xc2, 0x59, 0xdd, 0xfe] => { // thaw(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             thaw(context, state, account)
         }
+        [0x6c, 0xf2, 0x40, 0x90] => { // setAuthority(bytes32 account, uint8 authority_type, bytes32 new_authority)
+            let account = input.pubkey(0)?;
+            let authority_type = read_authority_type(input, 1)?;
+            let new_authority = Some(input.pubkey(2)?).filter(|key| *key != Pubkey::default());
+            set_authority(context, state, account, authority_type, new_authority)
+        }
+        [0xd9, 0x96, 0xd3, 0x5c] => { // syncNative(bytes32 account)
+            let account = input.pubkey(0)?;
+            sync_native(context, state, account)
+        }
+        [0xd6, 0xda, 0xd6, 0xea] => { // initializeMultisig(bytes32 seed, uint8 m, bytes32[] signers)
+            let seed = input.bytes32(0)?;
+            let m = input.u8(1)?;
+            let signers = read_pubkey_array(input, 2)?;
+            initialize_multisig(context, state, &seed, m, &signers)
+        }
+        [0xe7, 0x77, 0x47, 0x2f] => { // getAssociatedTokenAddress(bytes32 owner, bytes32 mint)
+            let owner = input.pubkey(0)?;
+            let mint = input.pubkey(1)?;
+            get_associated_token_address(context, state, owner, mint)
+        }
+        [0x1f, 0x3b, 0x67, 0x38] => { // createAssociatedTokenAccount(bytes32 owner, bytes32 mint)
+            let owner = input.pubkey(0)?;
+            let mint = input.pubkey(1)?;
+            create_associated_token_account(context, state, owner, mint)
+        }
         [0xeb, 0x7d, 0xa7, 0x8c] => { // findAccount(bytes32 seed)
-            let seed = read_salt(input);
-            find_account(context, state, seed)
+            let seed = input.bytes32(0)?;
+            find_account(context, state, &seed)
         }
         [0x38, 0xa6, 0x99, 0xa4] => { // exists(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             exists(context, state, account)
         }
         [0xd1, 0xde, 0x50, 0x11] => { // getAccount(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             get_account(context, state, account)
         }
         [0xa2, 0xce, 0x9c, 0x1f] => { // getMint(bytes32 account)
-            let account = read_pubkey(input);
+            let account = input.pubkey(0)?;
             get_mint(context, state, account)
         }
         _ => {
-            Ok(vec![])
+            Err!(ProgramError::InvalidInstructionData; "SPL Token: unknown method")
         }
-    };
+    });
 
-    result.map_or_else(
-        |_| Capture::Exit((ExitRevert::Reverted.into(), vec![])),
-        |value| Capture::Exit((ExitSucceed::Returned.into(), value))
-    )
-}
+    // Simulate queued instructions, so the contract sees their failure instead of the whole transaction failing
+    let result = result.and_then(|value| {
+        state.external_instructions_return_data()?;
+        Ok(value)
+    });
 
-
-#[inline]
-fn read_u8(input: &[u8]) -> u8 {
-    U256::from_big_endian_fast(arrayref::array_ref![input, 0, 32]).try_into().unwrap()
+    abi::exit(result)
 }
 
-#[inline]
-fn read_u64(input: &[u8]) -> u64 {
-    U256::from_big_endian_fast(arrayref::array_ref![input, 0, 32]).as_u64()
-}
 
-#[inline]
-fn read_pubkey(input: &[u8]) -> Pubkey {
-    Pubkey::new_from_array(*arrayref::array_ref![input, 0, 32])
+fn read_pubkey_array(input: Decoder<'_>, index: usize) -> abi::Result<Vec<Pubkey>> {
+    let (count, elements) = input.array(index)?;
+
+    (0..count).map(|i| elements.pubkey(i)).collect()
 }
 
-#[inline]
-fn read_salt(input: &[u8]) -> &[u8; 32] {
-    arrayref::array_ref![input, 0, 32]
+fn read_authority_type(input: Decoder<'_>, index: usize) -> abi::Result<AuthorityType> {
+    match input.u8(index)? {
+        0 => Ok(AuthorityType::MintTokens),
+        1 => Ok(AuthorityType::FreezeAccount),
+        2 => Ok(AuthorityType::AccountOwner),
+        3 => Ok(AuthorityType::CloseAccount),
+        _ => Err!(ProgramError::InvalidArgument; "SPL Token: invalid authority type"),
+    }
 }
 
 
-fn create_account<B: AccountStorage>(
+pub(super) fn create_account<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     account: &OwnedAccountInfo,
+    token_program: &Pubkey,
     space: usize,
     seeds: Vec<Vec<u8>>
-) -> Result<(), ProgramError> {
+) -> abi::Result<()> {
     let rent = Rent::get()?;
     let minimum_balance = rent.minimum_balance(space);
 
@@ -185,7 +252,7 @@ fn create_account<B: AccountStorage>(
         let allocate = system_instruction::allocate(&account.key, space.try_into().unwrap());
         state.queue_external_instruction(allocate, seeds.clone(), space);
 
-        let assign = system_instruction::assign(&account.key, &spl_token::ID);
+        let assign = system_instruction::assign(&account.key, token_program);
         state.queue_external_instruction(assign, seeds, 0);
     } else {
         let create_account = system_instruction::create_account(
@@ -193,7 +260,7 @@ fn create_account<B: AccountStorage>(
             &account.key,
             minimum_balance,
             space.try_into().unwrap(),
-            &spl_token::ID,
+            token_program,
         );
         state.queue_external_instruction(create_account, seeds, space);
     }
@@ -208,7 +275,7 @@ fn initialize_mint<B: AccountStorage>(
     decimals: u8,
     mint_authority: Option<Pubkey>,
     freeze_authority: Option<Pubkey>,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -233,7 +300,7 @@ fn initialize_mint<B: AccountStorage>(
         vec![bump_seed]
     ];
 
-    create_account(state, &account, spl_token::state::Mint::LEN, seeds)?;
+    create_account(state, &account, &spl_token::ID, spl_token::state::Mint::LEN, seeds)?;
 
     let initialize_mint = spl_token::instruction::initialize_mint(
         &spl_token::ID,
@@ -244,7 +311,7 @@ fn initialize_mint<B: AccountStorage>(
     )?;
     state.queue_external_instruction(initialize_mint, vec![], 0);
 
-    Ok(mint_key.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(mint_key.to_bytes())]))
 }
 
 fn initialize_account<B: AccountStorage>(
@@ -253,7 +320,7 @@ fn initialize_account<B: AccountStorage>(
     seed: &[u8],
     mint: Pubkey,
     owner: Option<Pubkey>,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -278,24 +345,165 @@ fn initialize_account<B: AccountStorage>(
         vec![bump_seed]
     ];
 
-    create_account(state, &account, spl_token::state::Account::LEN, seeds)?;
+    let mint_account = state.external_account(mint)?;
+    spl_token_2022::check_spl_token_program_account(&mint_account.owner)?;
 
-    let initialize_mint = spl_token::instruction::initialize_account2(
-        &spl_token::ID,
+    let token_program = mint_account.owner;
+    let space = token_account_space(&mint_account, &[])?;
+
+    create_account(state, &account, &token_program, space, seeds)?;
+
+    let initialize_mint = spl_token_2022::instruction::initialize_account2(
+        &token_program,
         &account_key,
         &mint,
         &owner.unwrap_or(signer_pubkey)
     )?;
     state.queue_external_instruction(initialize_mint, vec![], 0);
 
-    Ok(account_key.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(account_key.to_bytes())]))
+}
+
+fn initialize_multisig<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    seed: &[u8],
+    m: u8,
+    multisig_signers: &[Pubkey],
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+
+    let (multisig_key, bump_seed) = Pubkey::find_program_address(
+        &[ &[ACCOUNT_SEED_VERSION], b"ContractData", signer.as_bytes(), seed ],
+        state.backend.program_id()
+    );
+
+    let account = state.external_account(multisig_key)?;
+    if !solana_program::system_program::check_id(&account.owner) {
+        return Err!(ProgramError::IllegalOwner; "Account {} - is not system owned", multisig_key);
+    }
+
+    let seeds: Vec<Vec<u8>> = vec![
+        vec![ACCOUNT_SEED_VERSION], b"ContractData".to_vec(),
+        signer.as_bytes().to_vec(), seed.to_vec(),
+        vec![bump_seed]
+    ];
+
+    create_account(state, &account, &spl_token::ID, Multisig::LEN, seeds)?;
+
+    let multisig_signers: Vec<&Pubkey> = multisig_signers.iter().collect();
+    let initialize_multisig = spl_token::instruction::initialize_multisig2(
+        &spl_token::ID,
+        &multisig_key,
+        &multisig_signers,
+        m
+    )?;
+    state.queue_external_instruction(initialize_multisig, vec![], 0);
+
+    Ok(abi::encode(&[Token::FixedBytes(multisig_key.to_bytes())]))
+}
+
+/// Returns the authority and its signers.
+/// The authority is the contract itself, or a multisig account where the contract is one of the signers.
+fn authority<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    expected: Option<Pubkey>,
+    signer_pubkey: Pubkey,
+) -> abi::Result<(Pubkey, Vec<Pubkey>)>
+{
+    if let Some(expected) = expected.filter(|key| *key != signer_pubkey) {
+        let account = state.external_account(expected)?;
+        let is_token_program = spl_token_2022::check_spl_token_program_account(&account.owner).is_ok();
+        if is_token_program && (account.data.len() == Multisig::LEN) {
+            let multisig = Multisig::unpack(&account.data)?;
+            let signers = &multisig.signers[..usize::from(multisig.n)];
+
+            if signers.contains(&signer_pubkey) {
+                return Ok((expected, vec![signer_pubkey]));
+            }
+        }
+    }
+
+    Ok((signer_pubkey, vec![]))
+}
+
+/// Returns the size of the token account for the mint.
+/// Token-2022 mint extensions may require extensions of the token account.
+pub(super) fn token_account_space(mint_account: &OwnedAccountInfo, extensions: &[ExtensionType]) -> abi::Result<usize> {
+    if spl_token::check_id(&mint_account.owner) {
+        return Ok(Account::LEN);
+    }
+
+    let mint_extensions = StateWithExtensions::<Mint>::unpack(&mint_account.data)?.get_extension_types()?;
+
+    let mut account_extensions = ExtensionType::get_required_init_account_extensions(&mint_extensions);
+    account_extensions.extend_from_slice(extensions);
+
+    Ok(ExtensionType::get_account_len::<Account>(&account_extensions))
+}
+
+/// Returns the token account and its owning program: SPL Token or Token-2022
+pub(super) fn token_account<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    key: Pubkey,
+) -> abi::Result<(Pubkey, Account)>
+{
+    let account = state.external_account(key)?;
+    spl_token_2022::check_spl_token_program_account(&account.owner)?;
+
+    let token_account = StateWithExtensions::<Account>::unpack(&account.data)?.base;
+    Ok((account.owner, token_account))
+}
+
+/// Returns the mint and its owning program: SPL Token or Token-2022
+pub(super) fn token_mint<B: AccountStorage>(
+    state: &ExecutorState<B>,
+    key: Pubkey,
+) -> abi::Result<(Pubkey, Mint)>
+{
+    let account = state.external_account(key)?;
+    spl_token_2022::check_spl_token_program_account(&account.owner)?;
+
+    let mint = StateWithExtensions::<Mint>::unpack(&account.data)?.base;
+    Ok((account.owner, mint))
+}
+
+/// Returns the metadata address of the Token-2022 `MetadataPointer` mint extension.
+/// The extension is newer than the `spl_token_2022` crate version we depend on,
+/// so the TLV entries are walked by hand: base state padded to the token account size,
+/// account type, then `(type: u16, length: u16, value)`; the value is `authority (32), metadata_address (32)`
+fn metadata_pointer(data: &[u8]) -> Option<Pubkey> {
+    const EXTENSION_METADATA_POINTER: u16 = 18;
+
+    let mut tlv = data.get(Account::LEN + 1..)?;
+
+    while tlv.len() >= 4 {
+        let (header, rest) = tlv.split_at(4);
+        let entry_type = u16::from_le_bytes([header[0], header[1]]);
+        let entry_len = usize::from(u16::from_le_bytes([header[2], header[3]]));
+        let value = rest.get(..entry_len)?;
+
+        if entry_type == EXTENSION_METADATA_POINTER {
+            let address = value.get(32..64)?;
+            return Some(Pubkey::new_from_array(*arrayref::array_ref![address, 0, 32]));
+        }
+
+        tlv = &rest[entry_len..];
+    }
+
+    None
 }
 
 fn close_account<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     account: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -304,14 +512,18 @@ fn close_account<B: AccountStorage>(
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
 
+    let (token_program, token_account) = token_account(state, account)?;
+    let close_authority = token_account.close_authority.unwrap_or(token_account.owner);
+    let (authority, authority_signers) = authority(state, Some(close_authority), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let close_account = spl_token::instruction::close_account(
-        &spl_token::ID,
+    let close_account = spl_token_2022::instruction::close_account(
+        &token_program,
         &account,
         state.backend.operator(),
-        &signer_pubkey,
-        &[]
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>()
     )?;
     state.queue_external_instruction(close_account, seeds, 0);
 
@@ -324,7 +536,7 @@ fn approve<B: AccountStorage>(
     source: Pubkey,
     target: Pubkey,
     amount: u64,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -333,16 +545,23 @@ fn approve<B: AccountStorage>(
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
 
+    let (token_program, token_account) = token_account(state, source)?;
+    let (authority, authority_signers) = authority(state, Some(token_account.owner), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let approve = spl_token::instruction::approve(
-        &spl_token::ID,
-        &source,
-        &target,
-        &signer_pubkey,
-        &[],
-        amount
-    )?;
+    // Token-2022 mints with extensions reject unchecked instructions
+    let authority_signers: Vec<&Pubkey> = authority_signers.iter().collect();
+    let approve = if spl_token_2022::check_id(&token_program) {
+        let (_, mint) = token_mint(state, token_account.mint)?;
+        spl_token_2022::instruction::approve_checked(
+            &token_program, &source, &token_account.mint, &target, &authority, &authority_signers, amount, mint.decimals
+        )?
+    } else {
+        spl_token_2022::instruction::approve(
+            &token_program, &source, &target, &authority, &authority_signers, amount
+        )?
+    };
     state.queue_external_instruction(approve, seeds, 0);
 
     Ok(vec![])
@@ -352,7 +571,7 @@ fn revoke<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     account: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -361,13 +580,16 @@ fn revoke<B: AccountStorage>(
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
 
+    let (token_program, token_account) = token_account(state, account)?;
+    let (authority, authority_signers) = authority(state, Some(token_account.owner), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let revoke = spl_token::instruction::revoke(
-        &spl_token::ID,
+    let revoke = spl_token_2022::instruction::revoke(
+        &token_program,
         &account,
-        &signer_pubkey,
-        &[]
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>()
     )?;
     state.queue_external_instruction(revoke, seeds, 0);
 
@@ -380,7 +602,7 @@ fn transfer<B: AccountStorage>(
     source: Pubkey,
     target: Pubkey,
     amount: u64,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -389,15 +611,58 @@ fn transfer<B: AccountStorage>(
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
 
+    let (token_program, token_account) = token_account(state, source)?;
+    let (authority, authority_signers) = authority(state, Some(token_account.owner), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let transfer = spl_token::instruction::transfer(
-        &spl_token::ID,
+    // Token-2022 mints with extensions reject unchecked instructions
+    let authority_signers: Vec<&Pubkey> = authority_signers.iter().collect();
+    let transfer = if spl_token_2022::check_id(&token_program) {
+        let (_, mint) = token_mint(state, token_account.mint)?;
+        spl_token_2022::instruction::transfer_checked(
+            &token_program, &source, &token_account.mint, &target, &authority, &authority_signers, amount, mint.decimals
+        )?
+    } else {
+        spl_token_2022::instruction::transfer(
+            &token_program, &source, &target, &authority, &authority_signers, amount
+        )?
+    };
+    state.queue_external_instruction(transfer, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn transfer_checked<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    source: Pubkey,
+    target: Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let (token_program, token_account) = token_account(state, source)?;
+    let (authority, authority_signers) = authority(state, Some(token_account.owner), signer_pubkey)?;
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let transfer = spl_token_2022::instruction::transfer_checked(
+        &token_program,
         &source,
+        &token_account.mint,
         &target,
-        &signer_pubkey,
-        &[],
-        amount
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>(),
+        amount,
+        decimals
     )?;
     state.queue_external_instruction(transfer, seeds, 0);
 
@@ -409,7 +674,8 @@ fn mint<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     target: Pubkey,
     amount: u64,
-) -> Result<Vec<u8>, ProgramError>
+    decimals: Option<u8>,
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -418,21 +684,27 @@ fn mint<B: AccountStorage>(
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
 
-    let account = state.external_account(target)?;
-    spl_token::check_program_account(&account.owner)?;
-
-    let token_account = spl_token::state::Account::unpack(&account.data)?;
+    let (token_program, token_account) = token_account(state, target)?;
+    let (_, mint) = token_mint(state, token_account.mint)?;
+    let (authority, authority_signers) = authority(state, mint.mint_authority.into(), signer_pubkey)?;
 
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let mint_to = spl_token::instruction::mint_to(
-        &spl_token::ID,
-        &token_account.mint,
-        &target,
-        &signer_pubkey,
-        &[],
-        amount
-    )?;
+    // Token-2022 mints with extensions reject unchecked instructions
+    let decimals = match decimals {
+        None if spl_token_2022::check_id(&token_program) => Some(mint.decimals),
+        decimals => decimals,
+    };
+
+    let authority_signers: Vec<&Pubkey> = authority_signers.iter().collect();
+    let mint_to = match decimals {
+        Some(decimals) => spl_token_2022::instruction::mint_to_checked(
+            &token_program, &token_account.mint, &target, &authority, &authority_signers, amount, decimals
+        )?,
+        None => spl_token_2022::instruction::mint_to(
+            &token_program, &token_account.mint, &target, &authority, &authority_signers, amount
+        )?,
+    };
     state.queue_external_instruction(mint_to, seeds, 0);
 
     Ok(vec![])
@@ -443,7 +715,8 @@ fn burn<B: AccountStorage>(
     state: &mut ExecutorState<B>,
     source: Pubkey,
     amount: u64,
-) -> Result<Vec<u8>, ProgramError>
+    decimals: Option<u8>,
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -451,22 +724,27 @@ fn burn<B: AccountStorage>(
 
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
-    
-    let account = state.external_account(source)?;
-    spl_token::check_program_account(&account.owner)?;
-    
-    let token_account = spl_token::state::Account::unpack(&account.data)?;
-    
+
+    let (token_program, token_account) = token_account(state, source)?;
+    let (authority, authority_signers) = authority(state, Some(token_account.owner), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let burn = spl_token::instruction::burn(
-        &spl_token::ID,
-        &source,
-        &token_account.mint,
-        &signer_pubkey,
-        &[],
-        amount
-    )?;
+    // Token-2022 mints with extensions reject unchecked instructions
+    let decimals = match decimals {
+        None if spl_token_2022::check_id(&token_program) => Some(token_mint(state, token_account.mint)?.1.decimals),
+        decimals => decimals,
+    };
+
+    let authority_signers: Vec<&Pubkey> = authority_signers.iter().collect();
+    let burn = match decimals {
+        Some(decimals) => spl_token_2022::instruction::burn_checked(
+            &token_program, &source, &token_account.mint, &authority, &authority_signers, amount, decimals
+        )?,
+        None => spl_token_2022::instruction::burn(
+            &token_program, &source, &token_account.mint, &authority, &authority_signers, amount
+        )?,
+    };
     state.queue_external_instruction(burn, seeds, 0);
 
     Ok(vec![])
@@ -476,7 +754,7 @@ fn freeze<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     target: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
@@ -484,20 +762,19 @@ fn freeze<B: AccountStorage>(
 
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
-    
-    let account = state.external_account(target)?;
-    spl_token::check_program_account(&account.owner)?;
-    
-    let token_account = spl_token::state::Account::unpack(&account.data)?;
-    
+
+    let (token_program, token_account) = token_account(state, target)?;
+    let (_, mint) = token_mint(state, token_account.mint)?;
+    let (authority, authority_signers) = authority(state, mint.freeze_authority.into(), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let freeze = spl_token::instruction::freeze_account(
-        &spl_token::ID,
+    let freeze = spl_token_2022::instruction::freeze_account(
+        &token_program,
         &target,
         &token_account.mint,
-        &signer_pubkey,
-        &[],
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>(),
     )?;
     state.queue_external_instruction(freeze, seeds, 0);
 
@@ -508,40 +785,174 @@ fn thaw<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     target: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     if state.is_static_context() {
         return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
     }
-    
+
     let signer = context.caller;
     let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
-    
-    let account = state.external_account(target)?;
-    spl_token::check_program_account(&account.owner)?;
-    
-    let token_account = spl_token::state::Account::unpack(&account.data)?;
-    
+
+    let (token_program, token_account) = token_account(state, target)?;
+    let (_, mint) = token_mint(state, token_account.mint)?;
+    let (authority, authority_signers) = authority(state, mint.freeze_authority.into(), signer_pubkey)?;
+
     let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
 
-    let thaw = spl_token::instruction::thaw_account(
-        &spl_token::ID,
+    let thaw = spl_token_2022::instruction::thaw_account(
+        &token_program,
         &target,
         &token_account.mint,
-        &signer_pubkey,
-        &[],
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>(),
     )?;
     state.queue_external_instruction(thaw, seeds, 0);
 
     Ok(vec![])
 }
 
+fn set_authority<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    account: Pubkey,
+    authority_type: AuthorityType,
+    new_authority: Option<Pubkey>,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+
+    let (token_program, current_authority) = match authority_type {
+        AuthorityType::MintTokens => {
+            let (token_program, mint) = token_mint(state, account)?;
+            (token_program, mint.mint_authority.into())
+        }
+        AuthorityType::FreezeAccount => {
+            let (token_program, mint) = token_mint(state, account)?;
+            (token_program, mint.freeze_authority.into())
+        }
+        AuthorityType::AccountOwner => {
+            let (token_program, token_account) = token_account(state, account)?;
+            (token_program, Some(token_account.owner))
+        }
+        AuthorityType::CloseAccount => {
+            let (token_program, token_account) = token_account(state, account)?;
+            (token_program, Some(token_account.close_authority.unwrap_or(token_account.owner)))
+        }
+        _ => {
+            return Err!(ProgramError::InvalidArgument; "SPL Token: unsupported authority type")
+        }
+    };
+    let (authority, authority_signers) = authority(state, current_authority, signer_pubkey)?;
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let set_authority = spl_token_2022::instruction::set_authority(
+        &token_program,
+        &account,
+        new_authority.as_ref(),
+        authority_type,
+        &authority,
+        &authority_signers.iter().collect::<Vec<_>>(),
+    )?;
+    state.queue_external_instruction(set_authority, seeds, 0);
+
+    Ok(vec![])
+}
+
+fn sync_native<B: AccountStorage>(
+    _context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    account: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let (token_program, _) = token_account(state, account)?;
+
+    let sync_native = spl_token_2022::instruction::sync_native(&token_program, &account)?;
+    state.queue_external_instruction(sync_native, vec![], 0);
+
+    Ok(vec![])
+}
+
+fn get_associated_token_address<B: AccountStorage>(
+    _context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    owner: Pubkey,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    let mint_account = state.external_account(mint)?;
+    spl_token_2022::check_spl_token_program_account(&mint_account.owner)?;
+
+    let address = get_associated_token_address_with_program_id(&owner, &mint, &mint_account.owner);
+    Ok(abi::encode(&[Token::FixedBytes(address.to_bytes())]))
+}
+
+fn create_associated_token_account<B: AccountStorage>(
+    _context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    owner: Pubkey,
+    mint: Pubkey,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let address = associated_token_account(state, owner, mint)?;
+    Ok(abi::encode(&[Token::FixedBytes(address.to_bytes())]))
+}
+
+/// Returns the associated token account address,
+/// the account creation is queued if the account doesn't exist
+pub(super) fn associated_token_account<B: AccountStorage>(
+    state: &mut ExecutorState<B>,
+    owner: Pubkey,
+    mint: Pubkey,
+) -> abi::Result<Pubkey>
+{
+    let mint_account = state.external_account(mint)?;
+    spl_token_2022::check_spl_token_program_account(&mint_account.owner)?;
+
+    let token_program = mint_account.owner;
+    let address = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+
+    let account = state.external_account(address)?;
+    if account.owner != token_program {
+        let space = if spl_token::check_id(&token_program) {
+            Account::LEN
+        } else {
+            token_account_space(&mint_account, &[ExtensionType::ImmutableOwner])?
+        };
+
+        // The operator pays for the account, it is compensated by the transaction gas
+        let create_associated = create_associated_token_account_idempotent(
+            state.backend.operator(),
+            &owner,
+            &mint,
+            &token_program
+        );
+        state.queue_external_instruction(create_associated, vec![], space);
+    }
+
+    Ok(address)
+}
+
 #[allow(clippy::unnecessary_wraps)]
 fn find_account<B: AccountStorage>(
     context: &evm::Context,
     state: &mut ExecutorState<B>,
     seed: &[u8]
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let signer = context.caller;
 
@@ -550,80 +961,94 @@ fn find_account<B: AccountStorage>(
         state.backend.program_id()
     );
 
-    Ok(account_key.to_bytes().to_vec())
+    Ok(abi::encode(&[Token::FixedBytes(account_key.to_bytes())]))
 }
 
 fn exists<B: AccountStorage>(
     _context: &evm::Context,
     state: &mut ExecutorState<B>,
     account: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let account = state.external_account(account)?;
-    if solana_program::system_program::check_id(&account.owner) {
-        Ok(vec![0_u8; 32])
-    } else {
-        let mut result = vec![0_u8; 32];
-        result[31] = 1; // return true
+    let exists = !solana_program::system_program::check_id(&account.owner);
 
-        Ok(result)
-    }
+    Ok(abi::encode(&[Token::Bool(exists)]))
 }
 
 fn get_account<B: AccountStorage>(
     _context: &evm::Context,
     state: &mut ExecutorState<B>,
     account: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let account = state.external_account(account)?;
-    let token = if spl_token::check_id(&account.owner) {
-        spl_token::state::Account::unpack_unchecked(&account.data)?
+    let is_token_program = spl_token_2022::check_spl_token_program_account(&account.owner).is_ok();
+    let token = if is_token_program && (account.data.len() >= Account::LEN) {
+        Account::unpack_unchecked(&account.data[..Account::LEN])?
     } else {
-        spl_token::state::Account::default()
+        Account::default()
     };
 
     debug_print!("spl_token get_account: {:?}", token);
 
-    let mut result = [0_u8; 7*32];
-    let (mint, owner, _, amount, delegate, _, delegated_amount, close_authority, state) 
-        = arrayref::mut_array_refs![&mut result, 32, 32, 24, 8, 32, 24, 8, 32, 32];
-
-    *mint = token.mint.to_bytes();
-    *owner = token.owner.to_bytes();
-    *amount = token.amount.to_be_bytes();
-    *delegate = token.delegate.map(Pubkey::to_bytes).unwrap_or_default();
-    *delegated_amount = token.delegated_amount.to_be_bytes();
-    *close_authority = token.close_authority.map(Pubkey::to_bytes).unwrap_or_default();
-    state[31] = token.state as u8;
-
-    Ok(result.to_vec())
+    let withheld_amount = StateWithExtensions::<Account>::unpack(&account.data).ok()
+        .and_then(|state| state.get_extension::<TransferFeeAmount>().ok().map(|fee| u64::from(fee.withheld_amount)))
+        .unwrap_or(0);
+
+    Ok(abi::encode(&[
+        Token::FixedBytes(token.mint.to_bytes()),
+        Token::FixedBytes(token.owner.to_bytes()),
+        Token::uint(token.amount),
+        Token::FixedBytes(token.delegate.map(Pubkey::to_bytes).unwrap_or_default()),
+        Token::uint(token.delegated_amount),
+        Token::FixedBytes(token.close_authority.map(Pubkey::to_bytes).unwrap_or_default()),
+        Token::uint(token.state as u8),
+        Token::FixedBytes(if is_token_program { account.owner.to_bytes() } else { [0_u8; 32] }),
+        Token::uint(withheld_amount),
+    ]))
 }
 
 fn get_mint<B: AccountStorage>(
     _context: &evm::Context,
     state: &mut ExecutorState<B>,
     account: Pubkey,
-) -> Result<Vec<u8>, ProgramError>
+) -> abi::Result<Vec<u8>>
 {
     let account = state.external_account(account)?;
-    let mint = if spl_token::check_id(&account.owner) {
-        spl_token::state::Mint::unpack_unchecked(&account.data)?
+    let is_token_program = spl_token_2022::check_spl_token_program_account(&account.owner).is_ok();
+    let mint = if is_token_program && (account.data.len() >= Mint::LEN) {
+        Mint::unpack_unchecked(&account.data[..Mint::LEN])?
     } else {
-        spl_token::state::Mint::default()
+        Mint::default()
     };
 
     debug_print!("spl_token get_mint: {:?}", mint);
 
-    let mut result = [0_u8; 5*32];
-    let (_, supply, _, decimals, _, is_initialized, freeze_authority, mint_authority ) 
-        = arrayref::mut_array_refs![&mut result, 24, 8, 31, 1, 31, 1, 32, 32];
+    let epoch = EpochSchedule::get()?.get_epoch(state.backend.block_number().as_u64());
+    let (fee_basis_points, maximum_fee) = match StateWithExtensions::<Mint>::unpack(&account.data) {
+        Ok(mint_state) => mint_state.get_extension::<TransferFeeConfig>()
+            .map_or((0, 0), |config| transfer_fee(config, epoch)),
+        Err(_) => (0, 0),
+    };
 
-    *supply = mint.supply.to_be_bytes();
-    *decimals = mint.decimals.to_be_bytes();
-    *is_initialized = if mint.is_initialized { [1_u8] } else { [0_u8] };
-    *freeze_authority = mint.freeze_authority.map(Pubkey::to_bytes).unwrap_or_default();
-    *mint_authority = mint.mint_authority.map(Pubkey::to_bytes).unwrap_or_default();
+    let metadata_address = metadata_pointer(&account.data).unwrap_or_default();
+
+    Ok(abi::encode(&[
+        Token::uint(mint.supply),
+        Token::uint(mint.decimals),
+        Token::Bool(mint.is_initialized),
+        Token::FixedBytes(mint.freeze_authority.map(Pubkey::to_bytes).unwrap_or_default()),
+        Token::FixedBytes(mint.mint_authority.map(Pubkey::to_bytes).unwrap_or_default()),
+        Token::FixedBytes(if is_token_program { account.owner.to_bytes() } else { [0_u8; 32] }),
+        Token::uint(fee_basis_points),
+        Token::uint(maximum_fee),
+        Token::FixedBytes(metadata_address.to_bytes()),
+    ]))
+}
 
-    Ok(result.to_vec())
+/// Returns the transfer fee basis points and the maximum fee for the epoch of the executed block
+fn transfer_fee(config: &TransferFeeConfig, epoch: u64) -> (u16, u64) {
+    let fee = config.get_epoch_fee(epoch);
+    (u16::from(fee.transfer_fee_basis_points), u64::from(fee.maximum_fee))
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use std::convert::Infallible;
+
+use evm::{Capture, ExitReason, H160};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_instruction};
+use spl_token::native_mint;
+
+use crate::{
+    account::ACCOUNT_SEED_VERSION,
+    account_storage::AccountStorage,
+    executor::ExecutorState,
+};
+
+use super::abi::{self, Token};
+use super::spl_token::{associated_token_account, create_account};
+
+// [0xd0, 0xe3, 0x0d, 0xb0] : "deposit()"
+// [0x7f, 0x77, 0x45, 0xef] : "withdraw(bytes32,uint64)"
+// [0x88, 0x4e, 0x0a, 0xc2] : "solWallet(address)"
+//
+// SOL wallet is a system owned account of the contract, SOL is sent there on Solana side.
+// wSOL balance is kept in the associated token account of the contract's Solana address.
+
+#[must_use]
+pub fn wsol<B: AccountStorage>(
+    input: &[u8],
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> Capture<(ExitReason, Vec<u8>), Infallible>
+{
+    if !context.apparent_value.is_zero() {
+        return abi::revert("wSOL: value transfer is not allowed")
+    }
+
+    if context.address == context.caller {
+        // callcode is not allowed
+        return abi::revert("wSOL: callcode is not allowed")
+    }
+
+    if context.address != super::SYSTEM_ACCOUNT_WSOL {
+        // delegatecall is not allowed
+        return abi::revert("wSOL: delegatecall is not allowed")
+    }
+
+    let result = abi::split_selector(input).and_then(|(selector, input)| match selector {
+        [0xd0, 0xe3, 0x0d, 0xb0] => { // "deposit()"
+            deposit(context, state)
+        }
+        [0x7f, 0x77, 0x45, 0xef] => { // "withdraw(bytes32,uint64)"
+            let to = input.pubkey(0)?;
+            let lamports = input.u64(1)?;
+            withdraw(context, state, to, lamports)
+        }
+        [0x88, 0x4e, 0x0a, 0xc2] => { // "solWallet(address)"
+            let owner = input.address(0)?;
+            let (wallet, _) = sol_wallet(state.backend.program_id(), &owner);
+            Ok(abi::encode(&[Token::FixedBytes(wallet.to_bytes())]))
+        }
+        _ => {
+            Err!(ProgramError::InvalidInstructionData; "wSOL: unknown method")
+        }
+    });
+
+    abi::exit(result)
+}
+
+
+fn sol_wallet(program_id: &Pubkey, owner: &H160) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&[ACCOUNT_SEED_VERSION], b"SolWallet", owner.as_bytes()], program_id)
+}
+
+/// Moves all lamports of the caller's SOL wallet to the caller's wSOL account.
+/// Returns the deposited amount.
+fn deposit<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, _) = state.backend.solana_address(&signer);
+
+    let (wallet_key, wallet_bump_seed) = sol_wallet(state.backend.program_id(), &signer);
+    let wallet = state.external_account(wallet_key)?;
+    if !solana_program::system_program::check_id(&wallet.owner) || !wallet.data.is_empty() {
+        return Err!(ProgramError::IllegalOwner; "Account {} - is not system owned", wallet_key);
+    }
+
+    let wsol_account = associated_token_account(state, signer_pubkey, native_mint::id())?;
+
+    if wallet.lamports > 0 {
+        let wallet_seeds = vec![
+            vec![ACCOUNT_SEED_VERSION], b"SolWallet".to_vec(),
+            signer.as_bytes().to_vec(),
+            vec![wallet_bump_seed]
+        ];
+
+        let transfer = system_instruction::transfer(&wallet_key, &wsol_account, wallet.lamports);
+        state.queue_external_instruction(transfer, wallet_seeds, 0);
+    }
+
+    let sync_native = spl_token::instruction::sync_native(&spl_token::ID, &wsol_account)?;
+    state.queue_external_instruction(sync_native, vec![], 0);
+
+    Ok(abi::encode(&[Token::uint(wallet.lamports)]))
+}
+
+/// Unwraps wSOL of the caller and sends the lamports to the `to` address.
+/// Partial unwrap goes through a temporary wSOL account, its rent is lent by the operator
+/// and returned to the operator when the account is closed.
+/// Closed account is removed at the end of the transaction, so it allows one withdrawal per transaction.
+fn withdraw<B: AccountStorage>(
+    context: &evm::Context,
+    state: &mut ExecutorState<B>,
+    to: Pubkey,
+    lamports: u64,
+) -> abi::Result<Vec<u8>>
+{
+    if state.is_static_context() {
+        return Err!(ProgramError::InvalidArgument; "Action is not allowed in static context")
+    }
+
+    if lamports == 0 {
+        return Err!(ProgramError::InvalidArgument; "wSOL: withdraw amount must be greater than 0")
+    }
+
+    let signer = context.caller;
+    let (signer_pubkey, bump_seed) = state.backend.solana_address(&signer);
+    let operator = *state.backend.operator();
+
+    let wsol_account = spl_associated_token_account::get_associated_token_address(&signer_pubkey, &native_mint::id());
+
+    let (temporary_key, temporary_bump_seed) = Pubkey::find_program_address(
+        &[&[ACCOUNT_SEED_VERSION], b"SolWallet", signer.as_bytes(), b"Withdraw"],
+        state.backend.program_id()
+    );
+    let temporary = state.external_account(temporary_key)?;
+    if !solana_program::system_program::check_id(&temporary.owner) {
+        return Err!(ProgramError::IllegalOwner; "Account {} - is not system owned", temporary_key);
+    }
+
+    let temporary_seeds = vec![
+        vec![ACCOUNT_SEED_VERSION], b"SolWallet".to_vec(),
+        signer.as_bytes().to_vec(), b"Withdraw".to_vec(),
+        vec![temporary_bump_seed]
+    ];
+    create_account(state, &temporary, &spl_token::ID, spl_token::state::Account::LEN, temporary_seeds)?;
+
+    let initialize_account = spl_token::instruction::initialize_account3(
+        &spl_token::ID,
+        &temporary_key,
+        &native_mint::id(),
+        &signer_pubkey
+    )?;
+    state.queue_external_instruction(initialize_account, vec![], 0);
+
+    let seeds = vec![ vec![ACCOUNT_SEED_VERSION], signer.as_bytes().to_vec(), vec![bump_seed] ];
+
+    let transfer = spl_token::instruction::transfer_checked(
+        &spl_token::ID,
+        &wsol_account,
+        &native_mint::id(),
+        &temporary_key,
+        &signer_pubkey,
+        &[],
+        lamports,
+        native_mint::DECIMALS
+    )?;
+    state.queue_external_instruction(transfer, seeds.clone(), 0);
+
+    let close_account = spl_token::instruction::close_account(
+        &spl_token::ID,
+        &temporary_key,
+        &operator,
+        &signer_pubkey,
+        &[]
+    )?;
+    state.queue_external_instruction(close_account, seeds, 0);
+
+    let transfer = system_instruction::transfer(&operator, &to, lamports);
+    state.queue_external_instruction(transfer, vec![], 0);
+
+    // Fails the call right away if the wSOL balance is insufficient
+    state.external_instructions_return_data()?;
+
+    Ok(vec![])
+}
+
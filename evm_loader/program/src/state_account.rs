@@ -70,11 +70,12 @@ impl<'a> State<'a> {
             transaction_hash: trx.hash,
             caller,
             gas_limit: trx.gas_limit,
-            gas_price: trx.gas_price,
+            gas_price: trx.effective_gas_price(),
             gas_used: U256::zero(),
             operator: *accounts.operator.key,
             slot: Clock::get()?.slot,
             accounts_len: accounts.remaining_accounts.len(),
+            base_fee_per_gas: trx.base_fee_per_gas(),
         };
 
         info.data.borrow_mut()[0] = 0_u8;
@@ -1,6 +1,6 @@
 use std::convert::TryInto;
 
-use evm::{H160, U256};
+use evm::{H160, H256, U256};
 use solana_program::{
     entrypoint::{ProgramResult},
     program_error::{ProgramError},
@@ -9,14 +9,47 @@ use solana_program::{
 use crate::account_storage::ProgramAccountStorage;
 use crate::utils::{keccak256_digest};
 
+/// EIP-2718 transaction type of EIP-2930 access list transactions
+const ACCESS_LIST_TRANSACTION_TYPE: u8 = 0x01;
+/// EIP-2718 transaction type of EIP-1559 dynamic fee transactions
+const DYNAMIC_FEE_TRANSACTION_TYPE: u8 = 0x02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionType {
+    /// Untyped RLP list transaction (pre EIP-2718)
+    Legacy,
+    /// EIP-2930 transaction with an access list
+    AccessList,
+    /// EIP-1559 transaction with max fee and priority fee
+    DynamicFee,
+}
+
+impl TransactionType {
+    fn type_byte(self) -> Option<u8> {
+        match self {
+            TransactionType::Legacy => None,
+            TransactionType::AccessList => Some(ACCESS_LIST_TRANSACTION_TYPE),
+            TransactionType::DynamicFee => Some(DYNAMIC_FEE_TRANSACTION_TYPE),
+        }
+    }
+}
+
+/// List of addresses and storage keys the transaction plans to access
+pub type AccessList = Vec<(H160, Vec<U256>)>;
+
 #[derive(Debug)]
 pub struct Transaction {
+    pub transaction_type: TransactionType,
     pub nonce: u64,
+    /// Signed gas price. For EIP-1559 transactions equals to `max_fee_per_gas`
     pub gas_price: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
     pub gas_limit: U256,
     pub to: Option<H160>,
     pub value: U256,
     pub call_data: Vec<u8>,
+    pub access_list: AccessList,
     pub v: U256,
     pub r: [u8; 32],
     pub s: [u8; 32],
@@ -29,8 +62,113 @@ pub struct Transaction {
 
 impl Transaction {
     pub fn from_rlp(transaction: &[u8]) -> Result<Self, ProgramError> {
-        rlp::decode(transaction)
-            .map_err(|e| E!(ProgramError::InvalidInstructionData; "RLP DecoderError={}", e))
+        let result = match transaction.first() {
+            Some(&byte) if byte >= 0xC0 => rlp::decode(transaction),
+            Some(&ACCESS_LIST_TRANSACTION_TYPE) => {
+                Self::decode_typed_transaction(TransactionType::AccessList, &transaction[1..])
+            }
+            Some(&DYNAMIC_FEE_TRANSACTION_TYPE) => {
+                Self::decode_typed_transaction(TransactionType::DynamicFee, &transaction[1..])
+            }
+            Some(&byte) => {
+                return Err!(ProgramError::InvalidInstructionData; "Unsupported transaction type {:#04X}", byte);
+            }
+            None => {
+                return Err!(ProgramError::InvalidInstructionData; "Empty transaction");
+            }
+        };
+
+        result.map_err(|e| E!(ProgramError::InvalidInstructionData; "RLP DecoderError={}", e))
+    }
+
+    /// Gas price the transaction is charged with.
+    /// Legacy and EIP-2930 transactions pay the signed `gas_price`.
+    /// EIP-1559 transactions pay `min(max_fee_per_gas, base_fee_per_gas + max_priority_fee_per_gas)`
+    #[must_use]
+    pub fn effective_gas_price(&self) -> U256 {
+        match self.transaction_type {
+            TransactionType::Legacy | TransactionType::AccessList => self.gas_price,
+            TransactionType::DynamicFee => {
+                let base_fee = U256::from(crate::config::BASE_FEE_PER_GAS);
+                let price = base_fee.saturating_add(self.max_priority_fee_per_gas);
+                std::cmp::min(self.max_fee_per_gas, price)
+            }
+        }
+    }
+
+    /// Part of the gas price which is burned.
+    /// Only EIP-1559 transactions burn the base fee,
+    /// legacy and EIP-2930 transactions pay the whole gas price to the operator.
+    #[must_use]
+    pub fn base_fee_per_gas(&self) -> U256 {
+        match self.transaction_type {
+            TransactionType::Legacy | TransactionType::AccessList => U256::zero(),
+            TransactionType::DynamicFee => U256::from(crate::config::BASE_FEE_PER_GAS),
+        }
+    }
+
+    /// Decodes payload of EIP-2718 typed transaction:
+    /// EIP-2930: `rlp([chain_id, nonce, gas_price, gas_limit, to, value, data, access_list, y_parity, r, s])`
+    /// EIP-1559: `rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to, value, data, access_list, y_parity, r, s])`
+    fn decode_typed_transaction(transaction_type: TransactionType, payload: &[u8]) -> Result<Self, rlp::DecoderError> {
+        let rlp = rlp::Rlp::new(payload);
+        if !rlp.is_list() {
+            return Err(rlp::DecoderError::RlpExpectedToBeList);
+        }
+
+        let info = rlp.payload_info()?;
+        let payload_size = info.header_len + info.value_len;
+
+        let chain_id: U256 = rlp.val_at(0)?;
+        let nonce: u64 = rlp.val_at(1)?;
+
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas, offset) = match transaction_type {
+            TransactionType::DynamicFee => {
+                let max_priority_fee_per_gas: U256 = rlp.val_at(2)?;
+                let max_fee_per_gas: U256 = rlp.val_at(3)?;
+                (max_fee_per_gas, max_fee_per_gas, max_priority_fee_per_gas, 1)
+            }
+            TransactionType::Legacy | TransactionType::AccessList => {
+                let gas_price: U256 = rlp.val_at(2)?;
+                (gas_price, gas_price, gas_price, 0)
+            }
+        };
+
+        let gas_limit: U256 = rlp.val_at(3 + offset)?;
+        let to = decode_to(&rlp.at(4 + offset)?)?;
+        let value: U256 = rlp.val_at(5 + offset)?;
+        let call_data: Vec<u8> = rlp.val_at(6 + offset)?;
+        let access_list = decode_access_list(&rlp.at(7 + offset)?)?;
+
+        let recovery_id: u8 = rlp.val_at(8 + offset)?;
+        if recovery_id > 1 {
+            return Err(rlp::DecoderError::Custom("Invalid y_parity"));
+        }
+
+        let r = decode_signature_value(&rlp.at(9 + offset)?)?;
+        let s = decode_signature_value(&rlp.at(10 + offset)?)?;
+
+        let type_byte = [transaction_type.type_byte().expect("typed transaction")];
+
+        let raw = rlp.as_raw();
+        let hash = solana_program::keccak::hashv(&[&type_byte, &raw[..payload_size]]).to_bytes();
+
+        let (_, y_parity_offset) = rlp.at_with_offset(8 + offset)?;
+        let middle = &raw[info.header_len..y_parity_offset];
+        let header = rlp_list_header(middle.len());
+        let signed_hash = solana_program::keccak::hashv(&[&type_byte, &header, middle]).to_bytes();
+
+        let tx = Self {
+            transaction_type,
+            nonce, gas_price, max_fee_per_gas, max_priority_fee_per_gas, gas_limit,
+            to, value, call_data, access_list,
+            v: U256::from(recovery_id), r, s,
+            chain_id: Some(chain_id), recovery_id,
+            rlp_len: type_byte.len() + payload_size,
+            hash, signed_hash
+        };
+
+        Ok(tx)
     }
 }
 
@@ -42,31 +180,12 @@ impl rlp::Decodable for Transaction {
         let nonce: u64 = rlp.val_at(0)?;
         let gas_price: U256 = rlp.val_at(1)?;
         let gas_limit: U256 = rlp.val_at(2)?;
-        let to: Option<H160> = {
-            let to = rlp.at(3)?;
-            if to.is_empty() {
-                if to.is_data() {
-                    None
-                } else {
-                    return Err(rlp::DecoderError::RlpExpectedToBeData);
-                }
-            } else {
-                Some(to.as_val()?)
-            }
-        };
+        let to = decode_to(&rlp.at(3)?)?;
         let value: U256 = rlp.val_at(4)?;
         let call_data: Vec<u8> = rlp.val_at(5)?;
         let v: U256 = rlp.val_at(6)?;
-
-        let mut r: [u8; 32] = [0_u8; 32];
-        let r_src: &[u8] = rlp.at(7)?.data()?;
-        let r_pos: usize = r.len() - r_src.len();
-        r[r_pos..].copy_from_slice(r_src);
-
-        let mut s: [u8; 32] = [0_u8; 32];
-        let s_src: &[u8] = rlp.at(8)?.data()?;
-        let s_pos: usize = s.len() - s_src.len();
-        s[s_pos..].copy_from_slice(s_src);
+        let r = decode_signature_value(&rlp.at(7)?)?;
+        let s = decode_signature_value(&rlp.at(8)?)?;
 
         let (chain_id, recovery_id) = if v >= U256::from(35) {
             let chain_id = (v - 1) / 2 - 17;
@@ -85,7 +204,10 @@ impl rlp::Decodable for Transaction {
         let signed_hash = signed_hash(rlp, chain_id)?;
 
         let tx = Self {
-            nonce, gas_price, gas_limit, to, value, call_data, v, r, s,
+            transaction_type: TransactionType::Legacy,
+            nonce, gas_price, max_fee_per_gas: gas_price, max_priority_fee_per_gas: gas_price,
+            gas_limit, to, value, call_data,
+            access_list: AccessList::new(), v, r, s,
             chain_id, recovery_id, rlp_len: payload_size, hash, signed_hash
         };
 
@@ -93,6 +215,69 @@ impl rlp::Decodable for Transaction {
     }
 }
 
+fn decode_to(to: &rlp::Rlp) -> Result<Option<H160>, rlp::DecoderError> {
+    if to.is_empty() {
+        if to.is_data() {
+            Ok(None)
+        } else {
+            Err(rlp::DecoderError::RlpExpectedToBeData)
+        }
+    } else {
+        Ok(Some(to.as_val()?))
+    }
+}
+
+fn decode_signature_value(rlp: &rlp::Rlp) -> Result<[u8; 32], rlp::DecoderError> {
+    let src: &[u8] = rlp.data()?;
+    if src.len() > 32 {
+        return Err(rlp::DecoderError::RlpIsTooBig);
+    }
+
+    let mut value: [u8; 32] = [0_u8; 32];
+    let pos: usize = value.len() - src.len();
+    value[pos..].copy_from_slice(src);
+
+    Ok(value)
+}
+
+fn decode_access_list(rlp: &rlp::Rlp) -> Result<AccessList, rlp::DecoderError> {
+    if !rlp.is_list() {
+        return Err(rlp::DecoderError::RlpExpectedToBeList);
+    }
+
+    rlp.iter()
+        .map(|entry| {
+            let address: H160 = entry.val_at(0)?;
+            let keys: Vec<H256> = entry.list_at(1)?;
+            let keys = keys.into_iter()
+                .map(|key| U256::from_big_endian(key.as_bytes()))
+                .collect();
+
+            Ok((address, keys))
+        })
+        .collect()
+}
+
+fn rlp_list_header(len: usize) -> Vec<u8> {
+    if len <= 55 {
+        let len: u8 = len.try_into().unwrap();
+        vec![0xC0 + len]
+    } else {
+        let len_bytes = {
+            let leading_empty_bytes = (len.leading_zeros() as usize) / 8;
+            let bytes = len.to_be_bytes();
+            bytes[leading_empty_bytes..].to_vec()
+        };
+        let len_bytes_len: u8 = len_bytes.len().try_into().unwrap();
+
+        let mut header = Vec::with_capacity(10);
+        header.extend_from_slice(&[0xF7 + len_bytes_len]);
+        header.extend_from_slice(&len_bytes);
+
+        header
+    }
+}
+
 fn signed_hash(transaction: &rlp::Rlp, chain_id: Option<U256>) -> Result<[u8; 32], rlp::DecoderError> {
     let raw = transaction.as_raw();
     let payload_info = transaction.payload_info()?;
@@ -134,26 +319,7 @@ fn signed_hash(transaction: &rlp::Rlp, chain_id: Option<U256>) -> Result<[u8; 32
         }
     );
 
-    let header: Vec<u8> = {
-        let len = middle.len() + trailer.len();
-        if len <= 55 {
-            let len: u8 = len.try_into().unwrap();
-            vec![0xC0 + len]
-        } else {
-            let len_bytes = {
-                let leading_empty_bytes = (len.leading_zeros() as usize) / 8;
-                let bytes = len.to_be_bytes();
-                bytes[leading_empty_bytes..].to_vec()
-            };
-            let len_bytes_len: u8 = len_bytes.len().try_into().unwrap();
-
-            let mut header = Vec::with_capacity(10);
-            header.extend_from_slice(&[0xF7 + len_bytes_len]);
-            header.extend_from_slice(&len_bytes);
-
-            header
-        }
-    };
+    let header = rlp_list_header(middle.len() + trailer.len());
 
     let hash = solana_program::keccak::hashv(
         &[&header, middle, &trailer]
@@ -173,6 +339,23 @@ pub fn recover_caller_address(trx: &Transaction) -> Result<H160, ProgramError> {
     Ok(address)
 }
 
+/// Splits `used_gas * gas_price` into the base fee part, which is burned,
+/// and the priority part, which goes to the operator.
+/// The burned part is rounded down to whole SPL token units, the remainder goes to the operator.
+#[must_use]
+pub fn split_gas_cost(used_gas: U256, gas_price: U256, base_fee_per_gas: U256) -> (U256, U256) {
+    let base_fee_per_gas = std::cmp::min(gas_price, base_fee_per_gas);
+
+    // Can overflow in malicious transaction
+    let gas_cost = used_gas.saturating_mul(gas_price);
+    let base_fee = used_gas.saturating_mul(base_fee_per_gas);
+
+    let additional_decimals: u32 = (18 - crate::config::token_mint::decimals()).into();
+    let base_fee = base_fee - base_fee % U256::from(10_u64.pow(additional_decimals));
+
+    (base_fee, gas_cost - base_fee)
+}
+
 pub fn check_ethereum_transaction(
     account_storage: &ProgramAccountStorage,
     recovered_address: &H160,
@@ -185,12 +368,34 @@ pub fn check_ethereum_transaction(
         return Err!(ProgramError::InvalidArgument; "Invalid Ethereum transaction nonce: acc {}, trx {}", sender_account.trx_count, transaction.nonce);
     }
 
+    // Typed transactions (EIP-2718) always carry chain_id, legacy ones only after EIP-155
     if let Some(ref chain_id) = transaction.chain_id {
         if &U256::from(crate::config::CHAIN_ID) != chain_id {
             return Err!(ProgramError::InvalidArgument; "Invalid chain_id: actual {}, expected {}", chain_id, crate::config::CHAIN_ID);
         }
     }
 
+    match transaction.transaction_type {
+        TransactionType::AccessList if !crate::config::EVM_CONFIG.has_access_list => {
+            return Err!(ProgramError::InvalidArgument; "Access list transactions are not supported");
+        }
+        TransactionType::DynamicFee if !crate::config::EVM_CONFIG.has_base_fee => {
+            return Err!(ProgramError::InvalidArgument; "Dynamic fee transactions are not supported");
+        }
+        _ => {}
+    }
+
+    if transaction.transaction_type == TransactionType::DynamicFee
+        && transaction.max_priority_fee_per_gas > transaction.max_fee_per_gas
+    {
+        return Err!(ProgramError::InvalidArgument; "Max priority fee per gas {} exceeds max fee per gas {}", transaction.max_priority_fee_per_gas, transaction.max_fee_per_gas);
+    }
+
+    // For EIP-1559 transactions `gas_price` is `max_fee_per_gas`
+    if crate::config::EVM_CONFIG.has_base_fee && transaction.gas_price < U256::from(crate::config::BASE_FEE_PER_GAS) {
+        return Err!(ProgramError::InvalidArgument; "Gas price {} is below base fee {}", transaction.gas_price, crate::config::BASE_FEE_PER_GAS);
+    }
+
     match &transaction.to {
         Some(address) => {
             if !transaction.call_data.is_empty() {
@@ -219,3 +424,138 @@ pub fn check_ethereum_transaction(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signer of the test transactions, private key is `0x4646..46`
+    const SIGNER: &str = "9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+
+    fn decode(transaction: &str) -> Transaction {
+        Transaction::from_rlp(&hex::decode(transaction).unwrap()).unwrap()
+    }
+
+    fn address(value: &str) -> H160 {
+        H160::from_slice(&hex::decode(value).unwrap())
+    }
+
+    #[test]
+    fn legacy_eip155() {
+        // Example from EIP-155
+        let trx = decode(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+            8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f76\
+            1aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+
+        assert_eq!(trx.transaction_type, TransactionType::Legacy);
+        assert_eq!(trx.nonce, 9);
+        assert_eq!(trx.gas_price, U256::from(20_000_000_000_u64));
+        assert_eq!(trx.effective_gas_price(), trx.gas_price);
+        assert!(trx.base_fee_per_gas().is_zero());
+        assert_eq!(trx.gas_limit, U256::from(21_000));
+        assert_eq!(trx.to, Some(address("3535353535353535353535353535353535353535")));
+        assert_eq!(trx.value, U256::from(1_000_000_000_000_000_000_u64));
+        assert!(trx.call_data.is_empty());
+        assert!(trx.access_list.is_empty());
+        assert_eq!(trx.chain_id, Some(U256::one()));
+        assert_eq!(trx.recovery_id, 0);
+        assert_eq!(
+            hex::encode(trx.signed_hash),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(recover_caller_address(&trx).unwrap(), address(SIGNER));
+    }
+
+    #[test]
+    fn access_list_eip2930() {
+        let trx = decode(
+            "01f87d6f0885012a05f2008275309435353535353535353535353535353535353535350180d7d69422222222\
+            22222222222222222222222222222222c001a0e6041da34c8e2ced03b1ed5ecc371e301fe0d5f31063bea3\
+            d948d520270d2205a06c89ffed1ae5224aa88c6cb112f4c86ca726a12c166db19102336f8c6b21ed8c"
+        );
+
+        assert_eq!(trx.transaction_type, TransactionType::AccessList);
+        assert_eq!(trx.chain_id, Some(U256::from(111)));
+        assert_eq!(trx.nonce, 8);
+        assert_eq!(trx.gas_price, U256::from(5_000_000_000_u64));
+        assert_eq!(trx.effective_gas_price(), trx.gas_price);
+        assert!(trx.base_fee_per_gas().is_zero());
+        assert_eq!(trx.gas_limit, U256::from(30_000));
+        assert_eq!(trx.value, U256::one());
+        assert_eq!(trx.access_list, vec![(address("2222222222222222222222222222222222222222"), vec![])]);
+        assert_eq!(trx.rlp_len, 128);
+        assert_eq!(recover_caller_address(&trx).unwrap(), address(SIGNER));
+    }
+
+    #[test]
+    fn dynamic_fee_eip1559() {
+        let trx = decode(
+            "02f8ae6f07847735940084b2d05e00825208943535353535353535353535353535353535353535880de0b6\
+            b3a764000083abcdeff838f7941111111111111111111111111111111111111111e1a00000000000000000\
+            00000000000000000000000000000000000000000000000101a0dbb7b3e195048a328457e5774c069cb60f\
+            34da76d44d191c37adc5ec69c41f7aa02f481ed76ab5df282bf80a5cae18613a0412fc3717b42aff671ce0\
+            bf67be7e12"
+        );
+
+        assert_eq!(trx.transaction_type, TransactionType::DynamicFee);
+        assert_eq!(trx.chain_id, Some(U256::from(111)));
+        assert_eq!(trx.nonce, 7);
+        assert_eq!(trx.max_priority_fee_per_gas, U256::from(2_000_000_000_u64));
+        assert_eq!(trx.max_fee_per_gas, U256::from(3_000_000_000_u64));
+        assert_eq!(trx.gas_price, trx.max_fee_per_gas);
+        assert_eq!(
+            trx.effective_gas_price(),
+            std::cmp::min(
+                trx.max_fee_per_gas,
+                U256::from(crate::config::BASE_FEE_PER_GAS) + trx.max_priority_fee_per_gas
+            )
+        );
+        assert_eq!(trx.base_fee_per_gas(), U256::from(crate::config::BASE_FEE_PER_GAS));
+        assert_eq!(trx.call_data, vec![0xAB, 0xCD, 0xEF]);
+        assert_eq!(
+            trx.access_list,
+            vec![(address("1111111111111111111111111111111111111111"), vec![U256::one()])]
+        );
+        assert_eq!(recover_caller_address(&trx).unwrap(), address(SIGNER));
+    }
+
+    #[test]
+    fn unsupported_transaction_type() {
+        assert!(Transaction::from_rlp(&[0x03, 0xC0]).is_err());
+        assert!(Transaction::from_rlp(&[]).is_err());
+    }
+
+    #[test]
+    fn truncated_typed_transaction() {
+        let transaction = hex::decode("01f87d6f0885012a05f200827530").unwrap();
+        assert!(Transaction::from_rlp(&transaction).is_err());
+    }
+
+    #[test]
+    fn gas_cost_split() {
+        let used_gas = U256::from(21_000);
+        let gas_price = U256::from(3_000_000_000_u64);
+
+        let base_fee_per_gas = U256::from(crate::config::BASE_FEE_PER_GAS);
+
+        let (base_fee, priority_fee) = split_gas_cost(used_gas, gas_price, base_fee_per_gas);
+        assert_eq!(base_fee, used_gas * base_fee_per_gas);
+        assert_eq!(base_fee + priority_fee, used_gas * gas_price);
+
+        // Legacy and EIP-2930 transactions don't burn
+        let (base_fee, priority_fee) = split_gas_cost(used_gas, gas_price, U256::zero());
+        assert!(base_fee.is_zero());
+        assert_eq!(priority_fee, used_gas * gas_price);
+
+        // Only whole SPL token units are burned
+        let (base_fee, priority_fee) = split_gas_cost(U256::one(), gas_price, U256::one());
+        assert!(base_fee.is_zero());
+        assert_eq!(priority_fee, gas_price);
+
+        let (base_fee, priority_fee) = split_gas_cost(used_gas, U256::zero(), base_fee_per_gas);
+        assert!(base_fee.is_zero());
+        assert!(priority_fee.is_zero());
+    }
+}
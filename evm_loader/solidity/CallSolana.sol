@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity >= 0.7.0;
+pragma abicoder v2;
+
+// Precompile address: 0xff00000000000000000000000000000000000007
+//
+// Invokes a Solana program after the EVM execution succeeds.
+// Only the calling contract's Solana address can be marked as a signer.
+// Instructions of System, SPL Token, Associated Token and Metaplex programs are simulated immediately:
+// the call reverts if the instruction fails, the return data and the updated accounts are visible right away.
+// Other programs return empty data, the accounts they can write are not readable until the end of the transaction.
+// Only the programs from the network allowlist can be called.
+// The operator doesn't fund the instruction unless lamports are requested with the overload below.
+interface CallSolana {
+
+    struct AccountMeta {
+        bytes32 pubkey;
+        bool is_signer;
+        bool is_writable;
+    }
+
+    function callSolana(bytes32 program, AccountMeta[] memory accounts, bytes memory data) external returns (bytes memory);
+
+    // lamports - transferred by the operator to the first writable account before the instruction,
+    // e.g. rent of the account created by the instruction. Limited to the rent of 10240 bytes,
+    // the transaction gas covers them
+    function callSolana(bytes32 program, AccountMeta[] memory accounts, bytes memory data, uint64 lamports) external returns (bytes memory);
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity >= 0.7.0;
+pragma abicoder v2;
+
+// Precompile address: 0xff00000000000000000000000000000000000009
+//
+// Registers the native ERC-20 interface of an SPL Token or Token-2022 mint, no wrapper contract is deployed.
+// Anyone can register a mint, registration is idempotent and is paid by the operator.
+interface INativeERC20ForSplFactory {
+    function register(bytes32 mint) external returns(address);
+    // Address is known before the registration: 0xff000020 followed by the last 16 bytes of keccak256(mint)
+    function getAddress(bytes32 mint) external view returns(address);
+}
+
+// Implemented at the address returned by INativeERC20ForSplFactory.
+//
+// Balances are kept in the associated token accounts of the Neon accounts' Solana addresses.
+// SPL Token supports a single delegate per token account: approve() replaces the previous spender,
+// the allowance is capped to type(uint64).max. Amounts must fit into uint64.
+// Name and symbol are read from the Metaplex metadata of the mint.
+interface INativeERC20ForSpl {
+    event Transfer(address indexed from, address indexed to, uint256 amount);
+    event Approval(address indexed owner, address indexed spender, uint256 amount);
+
+    function name() external view returns (string memory);
+    function symbol() external view returns (string memory);
+    function decimals() external view returns (uint8);
+    function totalSupply() external view returns (uint256);
+    function balanceOf(address who) external view returns (uint256);
+    function allowance(address owner, address spender) external view returns (uint256);
+
+    function approve(address spender, uint256 amount) external returns (bool);
+    function transfer(address to, uint256 amount) external returns (bool);
+    function transferFrom(address from, address to, uint256 amount) external returns (bool);
+
+    function tokenMint() external view returns (bytes32);
+}
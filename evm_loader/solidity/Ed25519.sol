@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity >= 0.7.0;
+
+// Precompile address: 0xff00000000000000000000000000000000000006
+//
+// The signature is accepted if the Solana transaction contains an instruction
+// of the native Ed25519 program (Ed25519SigVerify111111111111111111111111111)
+// which verifies the same public key, message and signature.
+interface Ed25519 {
+    // signature must be 64 bytes long
+    function ed25519Verify(bytes32 pubkey, bytes memory message, bytes memory signature) external view returns (bool);
+}
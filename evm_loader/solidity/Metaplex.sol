@@ -5,11 +5,30 @@ pragma abicoder v2;
 
 interface Metaplex {
     function createMetadata(bytes32 _mint, string memory _name, string memory _symbol, string memory _uri) external returns(bytes32);
+    // Metadata created by the 4 arguments overload is immutable, `updateMetadata` requires `_isMutable = true`
+    function createMetadata(bytes32 _mint, string memory _name, string memory _symbol, string memory _uri, bool _isMutable) external returns(bytes32);
     function createMasterEdition(bytes32 mint, uint64 maxSupply) external returns(bytes32);
+    function updateMetadata(bytes32 mint, string memory name, string memory symbol, string memory uri, uint16 sellerFeeBasisPoints) external;
+    // Collection mint must have a master edition and the same update authority
+    function setCollection(bytes32 mint, bytes32 collectionMint) external;
+    function verifyCollection(bytes32 mint, bytes32 collectionMint) external;
+    function signMetadata(bytes32 mint) external;
+    // New mint must hold exactly one token, the caller must own the master edition token account
+    function printEdition(bytes32 masterMint, bytes32 newMint, bytes32 tokenAccount, uint64 edition) external returns(bytes32);
 
     function isInitialized(bytes32 mint) external view returns(bool);
     function isNFT(bytes32 mint) external view returns(bool);
     function uri(bytes32 mint) external view returns(string memory);
     function name(bytes32 mint) external view returns(string memory);
     function symbol(bytes32 mint) external view returns(string memory);
+
+    struct Creator {
+        bytes32 creator;
+        bool verified;
+        uint8 share;
+    }
+
+    function sellerFeeBasisPoints(bytes32 mint) external view returns(uint16);
+    function creators(bytes32 mint) external view returns(Creator[] memory);
+    function collection(bytes32 mint) external view returns(bytes32 collectionMint, bool verified);
 }
\ No newline at end of file
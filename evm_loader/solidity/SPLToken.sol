@@ -3,6 +3,10 @@
 pragma solidity >= 0.7.0;
 pragma abicoder v2;
 
+// Accounts and mints of both SPL Token and Token-2022 programs are supported,
+// new mints and multisigs are created by SPL Token program.
+// Operations are signed by the calling contract, or by the multisig account
+// where the contract is one of the signers
 interface SPLToken {
 
     enum AccountState {
@@ -19,6 +23,15 @@ interface SPLToken {
         uint64 delegated_amount;
         bytes32 close_authority;
         AccountState state;
+        bytes32 token_program;  // SPL Token or Token-2022
+        uint64 withheld_amount; // Token-2022 transfer fee withheld in the account
+    }
+
+    enum AuthorityType {
+        MintTokens,
+        FreezeAccount,
+        AccountOwner,
+        CloseAccount
     }
 
     struct Mint {
@@ -27,10 +40,18 @@ interface SPLToken {
         bool isInitialized;
         bytes32 freezeAuthority;
         bytes32 mintAuthority;
+        bytes32 tokenProgram;           // SPL Token or Token-2022
+        uint16 transferFeeBasisPoints;  // Token-2022 transfer fee for the current epoch
+        uint64 maximumFee;
+        bytes32 metadataAddress;        // Token-2022 metadata pointer
     }
 
     function findAccount(bytes32 salt) external pure returns(bytes32);
 
+    function getAssociatedTokenAddress(bytes32 owner, bytes32 mint) external view returns(bytes32);
+    // Does nothing if the account already exists, the operator pays for the account creation
+    function createAssociatedTokenAccount(bytes32 owner, bytes32 mint) external returns(bytes32);
+
     function exists(bytes32 account) external view returns(bool);
     function getAccount(bytes32 account) external view returns(Account memory);
     function getMint(bytes32 account) external view returns(Mint memory);
@@ -41,15 +62,26 @@ interface SPLToken {
     function initializeAccount(bytes32 salt, bytes32 mint) external returns(bytes32);
     function initializeAccount(bytes32 salt, bytes32 mint, bytes32 owner) external returns(bytes32);
 
+    function initializeMultisig(bytes32 salt, uint8 m, bytes32[] memory signers) external returns(bytes32);
+
     function closeAccount(bytes32 account) external;
 
     function mintTo(bytes32 account, uint64 amount) external;
     function burn(bytes32 account, uint64 amount) external;
+    function mintToChecked(bytes32 account, uint64 amount, uint8 decimals) external;
+    function burnChecked(bytes32 account, uint64 amount, uint8 decimals) external;
 
     function approve(bytes32 source, bytes32 target, uint64 amount) external;
     function revoke(bytes32 source) external;
 
     function transfer(bytes32 source, bytes32 target, uint64 amount) external;
+    function transferChecked(bytes32 source, bytes32 target, uint64 amount, uint8 decimals) external;
+
+    // new_authority = bytes32(0) removes the authority
+    function setAuthority(bytes32 account, AuthorityType authority_type, bytes32 new_authority) external;
+
+    // Updates wrapped SOL account amount to its lamports balance
+    function syncNative(bytes32 account) external;
 
     function freeze(bytes32 account) external;
     function thaw(bytes32 account) external;
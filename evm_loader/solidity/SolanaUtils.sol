@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity >= 0.7.0;
+pragma abicoder v2;
+
+// Precompile address: 0xff00000000000000000000000000000000000008
+interface SolanaUtils {
+    function createProgramAddress(bytes[] memory seeds, bytes32 program) external view returns(bytes32);
+    function findProgramAddress(bytes[] memory seeds, bytes32 program) external view returns(bytes32 account, uint8 bumpSeed);
+    function createWithSeed(bytes32 base, string memory seed, bytes32 owner) external view returns(bytes32);
+
+    function getClock() external view returns(uint64 slot, uint64 epoch, int64 unixTimestamp);
+    function minimumBalance(uint64 len) external view returns(uint64);
+    function getEpochSchedule() external view returns(
+        uint64 slotsPerEpoch,
+        uint64 leaderScheduleSlotOffset,
+        bool warmup,
+        uint64 firstNormalEpoch,
+        uint64 firstNormalSlot
+    );
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity >= 0.7.0;
+pragma abicoder v2;
+
+// Precompile address: 0xff0000000000000000000000000000000000000a
+//
+// Converts between SOL and wSOL of the calling contract.
+// SOL is sent on Solana side to the contract's SOL wallet, see solWallet().
+// wSOL is kept in the associated token account of the contract's Solana address.
+interface WrappedSOL {
+    function solWallet(address owner) external view returns(bytes32);
+
+    // Wraps all SOL of the wallet, creates wSOL account if needed. Returns the wrapped amount.
+    function deposit() external returns(uint64);
+    // Unwraps wSOL and sends SOL to the Solana account. Only one withdrawal per transaction.
+    function withdraw(bytes32 to, uint64 lamports) external;
+}
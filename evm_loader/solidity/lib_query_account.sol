@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 pragma solidity >=0.7.0;
+pragma abicoder v2;
 
 /**
  * @title QueryAccount
@@ -75,6 +76,78 @@ library QueryAccount {
         return precompiled.staticcall(abi.encodeWithSignature("data(uint256,uint64,uint64)", solana_address, offset, len));
     }
 
+    struct BatchRequest {
+        uint256 solana_address;
+        uint64 offset;
+        uint64 len;
+    }
+
+    struct BatchResult {
+        uint256 owner;
+        uint256 lamports;
+        bool executable;
+        uint256 length;
+        bytes data;
+    }
+
+    /**
+     * @dev Returns the metadata and a chunk of data for every request, no cache call is needed.
+     * @dev Several chunks of the same account are requested by repeating the account.
+     * @param requests Addresses of accounts, offsets and lengths of the chunks.
+     */
+    function batch(BatchRequest[] memory requests) internal view returns (bool, BatchResult[] memory) {
+        (bool success, bytes memory result) = precompiled.staticcall(abi.encodeWithSignature("batch((uint256,uint64,uint64)[])", requests));
+        if (!success) {
+            return (false, new BatchResult[](0));
+        }
+        return (true, abi.decode(result, (BatchResult[])));
+    }
+
+    struct TokenAccount {
+        uint256 mint;
+        uint256 owner;
+        uint64 amount;
+        uint256 delegate;
+        uint64 delegated_amount;
+        uint8 state;
+        bool is_native;
+        uint256 close_authority;
+    }
+
+    /**
+     * @dev Returns SPL Token or Token-2022 account, no cache call is needed.
+     * @param solana_address Address of a token account.
+     */
+    function token_account(uint256 solana_address) internal view returns (bool, TokenAccount memory) {
+        (bool success, bytes memory result) = precompiled.staticcall(abi.encodeWithSignature("tokenAccount(uint256)", solana_address));
+        if (!success) {
+            TokenAccount memory empty;
+            return (false, empty);
+        }
+        return (true, abi.decode(result, (TokenAccount)));
+    }
+
+    struct TokenMint {
+        uint256 mint_authority;
+        uint64 supply;
+        uint8 decimals;
+        bool is_initialized;
+        uint256 freeze_authority;
+    }
+
+    /**
+     * @dev Returns SPL Token or Token-2022 mint, no cache call is needed.
+     * @param solana_address Address of a token mint.
+     */
+    function token_mint(uint256 solana_address) internal view returns (bool, TokenMint memory) {
+        (bool success, bytes memory result) = precompiled.staticcall(abi.encodeWithSignature("tokenMint(uint256)", solana_address));
+        if (!success) {
+            TokenMint memory empty;
+            return (false, empty);
+        }
+        return (true, abi.decode(result, (TokenMint)));
+    }
+
     function to_uint256(bytes memory bb) private pure returns (uint256 result) {
         assembly {
             result := mload(add(bb, 32))
@@ -2,7 +2,13 @@
 pragma solidity >=0.7.6;
 
 interface INeonWithdraw {
-    function withdraw(bytes32) external payable returns(bool);
+    // Emitted by the precompile, `to` is the destination token account
+    event Withdraw(address indexed from, bytes32 indexed to, uint256 amount);
+
+    // Pays into the associated token account of the owner, the account is created if needed
+    function withdraw(bytes32 owner) external payable returns(bool);
+    // Pays into an existing token account of NEON mint
+    function withdrawTo(bytes32 tokenAccount) external payable returns(bool);
 }
 
 contract NeonToken {
@@ -11,4 +17,8 @@ contract NeonToken {
     function withdraw(bytes32 spender) external payable {
         NeonPrecompiled.withdraw{value: msg.value}(spender);
     }
-}
\ No newline at end of file
+
+    function withdrawTo(bytes32 tokenAccount) external payable {
+        NeonPrecompiled.withdrawTo{value: msg.value}(tokenAccount);
+    }
+}
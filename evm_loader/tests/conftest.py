@@ -7,7 +7,8 @@ import pytest
 from solana.keypair import Keypair
 from eth_keys import keys as eth_keys
 
-from .solana_utils import EvmLoader, OperatorAccount, create_treasury_pool_address, make_new_user, get_solana_balance
+from .solana_utils import EvmLoader, OperatorAccount, create_treasury_pool_address, make_new_user, get_solana_balance, \
+    deposit_neon
 from .utils.contract import deploy_contract
 from .utils.ethereum import Contract
 from .utils.types import TreasuryPool, Caller
@@ -58,13 +59,17 @@ def treasury_pool(evm_loader) -> TreasuryPool:
 
 
 @pytest.fixture(scope="function")
-def user_account(evm_loader) -> Caller:
-    return make_new_user(evm_loader)
+def user_account(evm_loader, operator_keypair) -> Caller:
+    user = make_new_user(evm_loader)
+    deposit_neon(evm_loader, operator_keypair, user.eth_address, 10 ** 9)
+    return user
 
 
 @pytest.fixture(scope="function")
-def second_user(evm_loader) -> Caller:
-    return make_new_user(evm_loader)
+def second_user(evm_loader, operator_keypair) -> Caller:
+    user = make_new_user(evm_loader)
+    deposit_neon(evm_loader, operator_keypair, user.eth_address, 10 ** 9)
+    return user
 
 
 @pytest.fixture(scope="function")
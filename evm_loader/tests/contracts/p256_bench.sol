@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT
+pragma solidity >=0.5.12;
+
+contract P256Bench {
+
+    function verify(bytes32 hash, bytes32 r, bytes32 s, bytes32 x, bytes32 y) public view returns(bool) {
+        (bool success, bytes memory result) = address(0x100).staticcall(abi.encode(hash, r, s, x, y));
+        return success && result.length == 32 && abi.decode(result, (uint256)) == 1;
+    }
+
+}
@@ -155,11 +155,12 @@ def test_create_program_address(evm_loader):
 
 def test_deposit(evm_loader, user_account):
     amount = random.randint(1, 100000)
+    balance_before = get_neon_balance(solana_client, user_account.solana_account_address)
     result = neon_cli().call(
         f"deposit --evm_loader {evm_loader.loader_id} {amount} {user_account.eth_address.hex()}").strip()
     balance_after = get_neon_balance(solana_client, user_account.solana_account_address)
     assert "CompiledInstruction" in result
-    assert balance_after == amount * 1000000000
+    assert balance_after - balance_before == amount * 1000000000
 
 
 def test_get_storage_at(evm_loader, operator_keypair, user_account, treasury_pool):
@@ -0,0 +1,55 @@
+import re
+
+from eth_utils import abi
+
+from .solana_utils import send_transaction, solana_client
+from .utils.constants import EVM_LOADER
+from .utils.contract import deploy_contract
+from .utils.ethereum import make_eth_transaction
+from .utils.instructions import TransactionWithComputeBudget, make_ExecuteTrxFromInstruction, DEFAULT_UNITS
+
+# hash || r || s || x || y, signed with a P-256 key over sha256("neon p256")
+P256_VECTOR = bytes.fromhex(
+    "44df3876e450091bc3686e01273ba592fa574b81095ee89d4a87e856f10dd6eb"
+    "35d58c8b93f94170fcf84120b4e16e2db6891b37cbdb52b251d0de8a50a88639"
+    "fe8591250de5e2e1eb901fa24fa5cbc8901793af67fe814b1a7b155bb7f5756a"
+    "9fad84aeae08bbef7f010014d82cef6a09de2b0cf871b5ce0c4f1d13a59a5934"
+    "07cb45769f1070e2c2470fe5b1bfe63133c0b0cdc64ea4bf3791a8ec2a07fd4f"
+)
+
+
+class TestP256Verify:
+    def test_p256_verify_fits_compute_budget(self, operator_keypair, user_account, evm_loader, treasury_pool):
+        """
+        P-256 verification is done in BPF code, check it fits into a single transaction compute budget
+        """
+        contract = deploy_contract(operator_keypair, user_account, "P256Bench.binary", evm_loader, treasury_pool)
+
+        func_name = abi.function_signature_to_4byte_selector('verify(bytes32,bytes32,bytes32,bytes32,bytes32)')
+        message = make_eth_transaction(
+            contract.eth_address,
+            func_name + P256_VECTOR,
+            user_account.solana_account,
+            user_account.solana_account_address,
+        ).rawTransaction
+
+        trx = TransactionWithComputeBudget()
+        trx.add(
+            make_ExecuteTrxFromInstruction(
+                operator_keypair,
+                evm_loader,
+                treasury_pool.account,
+                treasury_pool.buffer,
+                message,
+                [contract.solana_address, user_account.solana_account_address],
+            )
+        )
+        receipt = send_transaction(solana_client, trx, operator_keypair)
+        logs = receipt["result"]["meta"]["logMessages"]
+        assert "success" in logs[-1]
+
+        consumed = [
+            int(m.group(1)) for m in (re.search(rf"Program {EVM_LOADER} consumed (\d+) of", log) for log in logs) if m
+        ]
+        print("P-256 verification consumed compute units:", consumed)
+        assert consumed and max(consumed) < DEFAULT_UNITS
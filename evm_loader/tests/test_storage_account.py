@@ -7,7 +7,7 @@ from solana.rpc.core import RPCException
 from solana.transaction import Transaction
 
 from eth_utils import abi
-from .solana_utils import send_transaction, solana_client, get_transaction_count, make_new_user
+from .solana_utils import send_transaction, solana_client, get_transaction_count, make_new_user, deposit_neon
 from .utils.constants import TAG_STATE, TAG_FINALIZED_STATE
 from .utils.storage import create_holder
 from .utils.contract import deploy_contract
@@ -101,6 +101,7 @@ class TestStorageAccountAccess:
         parsed_data = STORAGE_ACCOUNT_INFO_LAYOUT.parse(account_data)
         assert parsed_data.tag == TAG_STATE
         user2 = make_new_user(evm_loader)
+        deposit_neon(evm_loader, operator_keypair, user2.eth_address, 10 ** 9)
         eth_transaction = make_eth_transaction(
             deployed_contract.eth_address,
             data,
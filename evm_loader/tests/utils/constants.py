@@ -26,3 +26,4 @@ TAG_HOLDER = 51
 SOLANA_URL = os.environ.get("SOLANA_URL", "http://localhost:8899")
 EVM_LOADER = os.environ.get("EVM_LOADER")
 NEON_TOKEN_MINT_ID: PublicKey = PublicKey(os.environ.get("NEON_TOKEN_MINT"))
+BASE_FEE_PER_GAS = 10 ** 9
@@ -10,6 +10,7 @@ from solana.rpc.types import TxOpts
 from solana.rpc.commitment import Confirmed
 from solana.transaction import Transaction
 
+from .constants import BASE_FEE_PER_GAS
 from .types import Caller, TreasuryPool
 from ..solana_utils import EVM_LOADER, solana_client, \
     send_transaction, create_account_with_seed, get_transaction_count, EvmLoader, \
@@ -37,7 +38,7 @@ def make_deployment_transaction(
         'to': None,
         'value': 0,
         'gas': gas,
-        'gasPrice': 0,
+        'gasPrice': BASE_FEE_PER_GAS,
         'nonce': get_transaction_count(solana_client, user.solana_account_address),
         'data': contract_code,
         'chainId': 111
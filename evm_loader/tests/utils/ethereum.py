@@ -6,7 +6,7 @@ from solana.keypair import Keypair
 from solana.publickey import PublicKey
 from web3.auto import w3
 
-from .constants import ACCOUNT_SEED_VERSION
+from .constants import ACCOUNT_SEED_VERSION, BASE_FEE_PER_GAS
 from .types import Caller
 from ..eth_tx_utils import pack
 from ..solana_utils import EvmLoader, solana_client, get_transaction_count
@@ -37,7 +37,7 @@ def create_contract_address(user: Caller, evm_loader: EvmLoader) -> Contract:
 
 def make_eth_transaction(to_addr: bytes, data: bytes, signer: Keypair, from_solana_user: PublicKey, value: int = 0):
     nonce = get_transaction_count(solana_client, from_solana_user)
-    tx = {'to': to_addr, 'value': value, 'gas': 9999999999, 'gasPrice': 0,
+    tx = {'to': to_addr, 'value': value, 'gas': 9999999999, 'gasPrice': BASE_FEE_PER_GAS,
           'nonce': nonce, 'data': data, 'chainId': 111}
 
     return w3.eth.account.sign_transaction(tx, signer.secret_key[:32])
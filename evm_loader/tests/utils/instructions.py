@@ -6,7 +6,7 @@ from solana.publickey import PublicKey
 from solana.system_program import SYS_PROGRAM_ID
 from solana.transaction import AccountMeta, TransactionInstruction, Transaction
 
-from .constants import EVM_LOADER, INCINERATOR_ADDRESS
+from .constants import EVM_LOADER, INCINERATOR_ADDRESS, NEON_TOKEN_MINT_ID
 
 DEFAULT_UNITS = 500 * 1000
 DEFAULT_HEAP_FRAME = 256 * 1024
@@ -207,3 +207,26 @@ def make_DepositV03(
     ]
 
     return TransactionInstruction(program_id=EVM_LOADER, data=data, keys=accounts)
+
+
+def make_DepositV04(
+    ether_address: bytes,
+    solana_account: PublicKey,
+    source: PublicKey,
+    pool: PublicKey,
+    token_program: PublicKey,
+    operator_pubkey: PublicKey,
+) -> TransactionInstruction:
+    data = bytes.fromhex('2a') + ether_address
+
+    accounts = [
+        AccountMeta(pubkey=source, is_signer=False, is_writable=True),
+        AccountMeta(pubkey=pool, is_signer=False, is_writable=True),
+        AccountMeta(pubkey=solana_account, is_signer=False, is_writable=True),
+        AccountMeta(pubkey=token_program, is_signer=False, is_writable=False),
+        AccountMeta(pubkey=operator_pubkey, is_signer=True, is_writable=True),
+        AccountMeta(pubkey=SYS_PROGRAM_ID, is_signer=False, is_writable=False),
+        AccountMeta(pubkey=NEON_TOKEN_MINT_ID, is_signer=False, is_writable=False),
+    ]
+
+    return TransactionInstruction(program_id=EVM_LOADER, data=data, keys=accounts)
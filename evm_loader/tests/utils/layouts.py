@@ -11,6 +11,7 @@ STORAGE_ACCOUNT_INFO_LAYOUT = Struct(
     "operator" / Bytes(32),
     "slot" / Int64ul,
     "account_list_len" / Int64ul,
+    "base_fee_per_gas" / Bytes(32),
 )
 
 